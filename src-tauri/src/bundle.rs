@@ -0,0 +1,417 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::language::LanguageSpec;
+
+/// Where the solution called `name` is written for copy-pasting into AtCoder.
+pub fn submission_path(language: &LanguageSpec, workspace: &Path, name: &str) -> PathBuf {
+    workspace
+        .join("submit")
+        .join(format!("{}.{}", name, language.extension))
+}
+
+/// Writes the bundle of `source` to `destination`, returning whether there was
+/// anything to bundle. Nothing is written for a source that uses no local file.
+pub fn write_bundle(
+    language: &LanguageSpec,
+    source: &Path,
+    workspace: &Path,
+    destination: &Path,
+) -> Result<bool, String> {
+    let Some(bundled) = bundle(language, source, workspace)? else {
+        return Ok(false);
+    };
+    fs::create_dir_all(destination.parent().unwrap())
+        .map_err(|err| format!("error while creating bundle folder: {}", err))?;
+    fs::write(destination, bundled)
+        .map_err(|err| format!("error while writing bundled source: {}", err))?;
+    Ok(true)
+}
+
+/// Inlines the local files a solution pulls in, so that it can be submitted as
+/// a single file. Files are looked up next to the file using them first, then
+/// in the workspace, so that a library kept in the workspace can be shared by
+/// every solution.
+///
+/// Returns `None` for languages that have nothing to bundle and for sources
+/// that do not use any local file, those are built and submitted as they are.
+/// C and C++ bundles carry `#line` markers, so that compiler errors still
+/// point into the files that were inlined. Their paths are relative to the
+/// workspace, which the compiler runs in, as the bundle gets submitted.
+pub fn bundle(
+    language: &LanguageSpec,
+    source: &Path,
+    workspace: &Path,
+) -> Result<Option<String>, String> {
+    match language.id.as_str() {
+        "c" | "cpp" => {
+            let mut bundler = CppBundler {
+                workspace,
+                root: workspace
+                    .canonicalize()
+                    .unwrap_or_else(|_| workspace.to_path_buf()),
+                included: HashSet::new(),
+                stack: source.canonicalize().into_iter().collect(),
+                inlined: false,
+            };
+            let expanded = bundler.expand(source)?;
+            Ok(bundler
+                .inlined
+                .then(|| format!("{}{}", bundler.line_marker(1, source), expanded)))
+        }
+        "rust" => {
+            let directory = source.parent().unwrap_or(workspace);
+            let mut bundler = RustBundler {
+                workspace,
+                inlined: false,
+            };
+            let expanded = bundler.expand(
+                source,
+                directory,
+                &mut source.canonicalize().into_iter().collect(),
+            )?;
+            Ok(bundler.inlined.then_some(expanded))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|err| format!("error while reading {}: {}", path.display(), err))
+}
+
+fn find(candidates: &[PathBuf]) -> Option<PathBuf> {
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| candidate.canonicalize().ok())
+}
+
+struct CppBundler<'a> {
+    workspace: &'a Path,
+    /// the workspace resolved, for the paths in `#line` markers
+    root: PathBuf,
+    /// files that are only ever included once, through `#pragma once` or a
+    /// guard
+    included: HashSet<PathBuf>,
+    /// files being expanded, to report include cycles
+    stack: Vec<PathBuf>,
+    /// whether any file was inlined
+    inlined: bool,
+}
+
+impl CppBundler<'_> {
+    fn expand(&mut self, path: &Path) -> Result<String, String> {
+        let text = read(path)?;
+        let directory = path.parent().unwrap_or(self.workspace);
+        let mut expanded = String::new();
+        // lines that are dropped are left empty, so that only inlined files
+        // need a `#line` marker to keep the numbering right
+        for (number, line) in (1..).zip(text.lines()) {
+            if is_pragma_once(line) {
+                expanded.push('\n');
+                continue;
+            }
+            let Some(include) = local_include(line) else {
+                expanded.push_str(line);
+                expanded.push('\n');
+                continue;
+            };
+            // left alone when not found, it may come from an include path
+            let Some(file) = find(&[directory.join(include), self.workspace.join(include)]) else {
+                expanded.push_str(line);
+                expanded.push('\n');
+                continue;
+            };
+            if self.included.contains(&file) {
+                expanded.push('\n');
+                continue;
+            }
+            if self.stack.contains(&file) {
+                return Err(format!(
+                    "error while bundling: {} includes itself without a guard",
+                    file.display()
+                ));
+            }
+
+            let contents = read(&file)?;
+            if contents.lines().any(is_pragma_once) || has_include_guard(&contents) {
+                self.included.insert(file.clone());
+            }
+            self.stack.push(file.clone());
+            self.inlined = true;
+            expanded.push_str(&format!("// begin {}\n", include));
+            expanded.push_str(&self.line_marker(1, &file));
+            expanded.push_str(&self.expand(&file)?);
+            expanded.push_str(&format!("// end {}\n", include));
+            expanded.push_str(&self.line_marker(number + 1, path));
+            self.stack.pop();
+        }
+        Ok(expanded)
+    }
+
+    /// Makes the line after it count as line `number` of `path` in
+    /// diagnostics. Files outside the workspace go by their name alone, so
+    /// that no local path ends up in a submission.
+    fn line_marker(&self, number: usize, path: &Path) -> String {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) => Path::new(path.file_name().unwrap_or_default()),
+        };
+        let relative: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect();
+        format!("#line {} \"{}\"\n", number, relative.join("/"))
+    }
+}
+
+/// The path of `#include "path"`, `None` for `#include <path>` and other lines.
+fn local_include(line: &str) -> Option<&str> {
+    let directive = line.trim().strip_prefix('#')?.trim_start();
+    let path = directive.strip_prefix("include")?.trim_start();
+    path.strip_prefix('"')?.split('"').next()
+}
+
+fn is_pragma_once(line: &str) -> bool {
+    line.trim()
+        .strip_prefix('#')
+        .is_some_and(|directive| directive.split_whitespace().eq(["pragma", "once"]))
+}
+
+/// Whether the file starts with `#ifndef NAME` followed by `#define NAME`.
+fn has_include_guard(contents: &str) -> bool {
+    let mut directives = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"));
+    let guard = |line: Option<&str>, directive: &str| -> Option<String> {
+        let words: Vec<&str> = line?.strip_prefix('#')?.split_whitespace().collect();
+        match words[..] {
+            [name, guard] if name == directive => Some(guard.to_string()),
+            _ => None,
+        }
+    };
+    match (
+        guard(directives.next(), "ifndef"),
+        guard(directives.next(), "define"),
+    ) {
+        (Some(ifndef), Some(define)) => ifndef == define,
+        _ => false,
+    }
+}
+
+struct RustBundler<'a> {
+    workspace: &'a Path,
+    /// whether any module was inlined
+    inlined: bool,
+}
+
+impl RustBundler<'_> {
+    /// Expands the file of a module whose own modules are in `directory`.
+    fn expand(
+        &mut self,
+        path: &Path,
+        directory: &Path,
+        stack: &mut Vec<PathBuf>,
+    ) -> Result<String, String> {
+        let text = read(path)?;
+        let mut expanded = String::new();
+        for line in text.lines() {
+            let Some((declaration, name)) = module_declaration(line) else {
+                expanded.push_str(line);
+                expanded.push('\n');
+                continue;
+            };
+            let candidates = [
+                directory.join(format!("{}.rs", name)),
+                directory.join(name).join("mod.rs"),
+                self.workspace.join(format!("{}.rs", name)),
+                self.workspace.join(name).join("mod.rs"),
+            ];
+            let Some(file) = find(&candidates) else {
+                return Err(format!(
+                    "error while bundling: file for module {} not found",
+                    name
+                ));
+            };
+            if stack.contains(&file) {
+                return Err(format!(
+                    "error while bundling: {} declares itself as a module",
+                    file.display()
+                ));
+            }
+
+            // both `name.rs` and `name/mod.rs` keep their modules in `name/`
+            let parent = file.parent().unwrap();
+            let children = if file.ends_with("mod.rs") {
+                parent.to_path_buf()
+            } else {
+                parent.join(name)
+            };
+            stack.push(file.clone());
+            self.inlined = true;
+            let indent = &line[..line.len() - line.trim_start().len()];
+            expanded.push_str(&format!("{}{} {{\n", indent, declaration));
+            expanded.push_str(&self.expand(&file, &children, stack)?);
+            expanded.push_str(&format!("{}}}\n", indent));
+            stack.pop();
+        }
+        Ok(expanded)
+    }
+}
+
+/// Splits `pub mod name;` into `pub mod name` and `name`.
+fn module_declaration(line: &str) -> Option<(&str, &str)> {
+    let declaration = line.trim().strip_suffix(';')?.trim_end();
+    let (visibility, name) = declaration.rsplit_once("mod ")?;
+    let visibility = visibility.trim();
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_');
+    (valid_name && (visibility.is_empty() || visibility.starts_with("pub")))
+        .then_some((declaration, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageRegistry;
+
+    /// Writes `files` into a fresh workspace under the temp dir.
+    fn workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let workspace =
+            std::env::temp_dir().join(format!("xcoder_bundle_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&workspace);
+        for (path, contents) in files {
+            let path = workspace.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        workspace
+    }
+
+    fn bundle_source(language: &str, workspace: &Path, source: &str) -> Option<String> {
+        let languages = LanguageRegistry::default();
+        let language = languages.get(language).unwrap();
+        bundle(language, &workspace.join(source), workspace).unwrap()
+    }
+
+    #[test]
+    fn nothing_to_bundle() {
+        let workspace = workspace(
+            "nothing",
+            &[
+                ("abc.cpp", "#include <cstdio>\nint main() {}\n"),
+                ("abc.rs", "use std::io;\nfn main() {}\n"),
+            ],
+        );
+        assert_eq!(bundle_source("cpp", &workspace, "abc.cpp"), None);
+        assert_eq!(bundle_source("rust", &workspace, "abc.rs"), None);
+    }
+
+    #[test]
+    fn pragma_once_is_included_once() {
+        let workspace = workspace(
+            "pragma",
+            &[
+                ("lib.hpp", "#pragma once\nint f() { return 1; }\n"),
+                (
+                    "cpp/abc.cpp",
+                    "#include \"lib.hpp\"\n#include \"lib.hpp\"\nint main() {}\n",
+                ),
+            ],
+        );
+        let bundled = bundle_source("cpp", &workspace, "cpp/abc.cpp").unwrap();
+        assert_eq!(bundled.matches("int f()").count(), 1);
+        assert!(!bundled.contains("#pragma once"));
+    }
+
+    #[test]
+    fn include_guard_is_included_once() {
+        let workspace = workspace(
+            "guard",
+            &[
+                (
+                    "lib.hpp",
+                    "// shared helpers\n#ifndef LIB_HPP\n#define LIB_HPP\nint f() { return 1; }\n#endif\n",
+                ),
+                ("abc.cpp", "#include \"lib.hpp\"\n#include \"lib.hpp\"\nint main() {}\n"),
+            ],
+        );
+        let bundled = bundle_source("cpp", &workspace, "abc.cpp").unwrap();
+        assert_eq!(bundled.matches("int f()").count(), 1);
+    }
+
+    #[test]
+    fn line_numbers_continue_after_an_include() {
+        let workspace = workspace(
+            "lines",
+            &[
+                ("lib.hpp", "#pragma once\nint f() { return 1; }\n"),
+                (
+                    "abc.cpp",
+                    "#include <cstdio>\n#include \"lib.hpp\"\nint main() {}\n",
+                ),
+            ],
+        );
+        let bundled = bundle_source("cpp", &workspace, "abc.cpp").unwrap();
+        assert_eq!(
+            bundled,
+            "#line 1 \"abc.cpp\"\n\
+             #include <cstdio>\n\
+             // begin lib.hpp\n\
+             #line 1 \"lib.hpp\"\n\
+             \n\
+             int f() { return 1; }\n\
+             // end lib.hpp\n\
+             #line 3 \"abc.cpp\"\n\
+             int main() {}\n"
+        );
+    }
+
+    #[test]
+    fn include_cycle_is_an_error() {
+        let workspace = workspace(
+            "cycle",
+            &[
+                ("a.hpp", "#include \"b.hpp\"\n"),
+                ("b.hpp", "#include \"a.hpp\"\n"),
+                ("abc.cpp", "#include \"a.hpp\"\nint main() {}\n"),
+            ],
+        );
+        let languages = LanguageRegistry::default();
+        let result = bundle(
+            languages.get("cpp").unwrap(),
+            &workspace.join("abc.cpp"),
+            &workspace,
+        );
+        assert!(result.unwrap_err().contains("includes itself"));
+    }
+
+    #[test]
+    fn nested_modules() {
+        let workspace = workspace(
+            "modules",
+            &[
+                ("src/a.rs", "pub mod b;\n"),
+                ("src/a/b.rs", "pub fn f() {}\n"),
+                ("src/abc.rs", "mod a;\nfn main() {\n    a::b::f();\n}\n"),
+            ],
+        );
+        let bundled = bundle_source("rust", &workspace, "src/abc.rs").unwrap();
+        assert_eq!(
+            bundled,
+            "mod a {\n\
+             pub mod b {\n\
+             pub fn f() {}\n\
+             }\n\
+             }\n\
+             fn main() {\n    a::b::f();\n}\n"
+        );
+    }
+}
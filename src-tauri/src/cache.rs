@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+use sha2::{Digest, Sha256};
+
+use crate::detect::read_version;
+use crate::language::LanguageSpec;
+
+/// Versions already asked from each compiler, as starting some of them (kotlinc,
+/// swiftc) takes longer than the build they would save.
+static VERSIONS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// Hashes everything that goes into a build: the source, the language, the
+/// version of the compiler and the flags and environment it is called with.
+pub fn compile_key(
+    language: &LanguageSpec,
+    compiler: &Command,
+    source: &Path,
+) -> Result<String, String> {
+    let program = compiler.get_program().to_string_lossy().to_string();
+    let contents =
+        fs::read(source).map_err(|err| format!("error while reading source file: {}", err))?;
+
+    let mut hasher = Sha256::new();
+    for part in [
+        language.id.as_bytes(),
+        program.as_bytes(),
+        compiler_version(&language.version_command()).as_bytes(),
+    ] {
+        hasher.update(part);
+        hasher.update([0]);
+    }
+    for arg in compiler.get_args() {
+        hasher.update(arg.to_string_lossy().as_bytes());
+        hasher.update([0]);
+    }
+    let mut envs: Vec<_> = compiler.get_envs().collect();
+    envs.sort();
+    for (key, value) in envs {
+        hasher.update(key.to_string_lossy().as_bytes());
+        hasher.update([b'=']);
+        hasher.update(value.unwrap_or_default().to_string_lossy().as_bytes());
+        hasher.update([0]);
+    }
+    hasher.update(&contents);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Whether `binary` was built from a source and toolchain hashing to `key`.
+pub fn is_fresh(binary: &Path, key: &str) -> bool {
+    binary.exists() && fs::read_to_string(stamp_path(binary)).is_ok_and(|stamp| stamp == key)
+}
+
+/// Records the key `binary` was built with, next to it in `bin/`.
+pub fn save_key(binary: &Path, key: &str) -> Result<(), String> {
+    fs::write(stamp_path(binary), key)
+        .map_err(|err| format!("error while writing compile cache: {}", err))
+}
+
+/// Forgets the key of `binary`, so that a failed build is never taken for a
+/// cached one.
+pub fn remove_key(binary: &Path) {
+    let _ = fs::remove_file(stamp_path(binary));
+}
+
+/// `abc123_A.hash` for `abc123_A` and `abc123_A.jar.hash` for `abc123_A.jar`,
+/// so that binaries of different languages keep stamps of their own.
+fn stamp_path(binary: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.hash", binary.display()))
+}
+
+fn compiler_version(command: &[String]) -> String {
+    let versions = VERSIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    let key = command.join(" ");
+    if let Some(version) = versions.lock().unwrap().get(&key) {
+        return version.clone();
+    }
+
+    // a compiler that cannot report its version still gets a key, it is just
+    // not invalidated when the compiler is upgraded
+    let version = read_version(command).unwrap_or_default();
+    versions.lock().unwrap().insert(key, version.clone());
+    version
+}
@@ -0,0 +1,278 @@
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runner::no_window;
+
+/// Built-in ways of comparing the output of a solution with the answer.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Checker {
+    /// whitespace separated tokens have to match, like on atcoder
+    #[default]
+    Tokens,
+    /// the whole trimmed output has to match the answer byte for byte
+    Strict,
+    /// tokens that are numbers may differ by an absolute or relative error
+    Float { absolute: f64, relative: f64 },
+    /// tokens have to match ignoring ascii case, for `Yes`/`YES` style answers
+    CaseInsensitive,
+}
+
+pub struct CheckResult {
+    pub accepted: bool,
+    pub message: Option<String>,
+}
+
+impl CheckResult {
+    fn accepted() -> CheckResult {
+        CheckResult {
+            accepted: true,
+            message: None,
+        }
+    }
+
+    fn rejected(message: String) -> CheckResult {
+        CheckResult {
+            accepted: false,
+            message: Some(message),
+        }
+    }
+}
+
+impl Checker {
+    pub fn check(&self, output: &str, answer: &str) -> CheckResult {
+        match self {
+            Checker::Strict => {
+                if output == answer {
+                    CheckResult::accepted()
+                } else {
+                    CheckResult::rejected("output differs from the answer".into())
+                }
+            }
+            Checker::Tokens => compare_tokens(output, answer, |out, ans| out == ans),
+            Checker::CaseInsensitive => {
+                compare_tokens(output, answer, |out, ans| out.eq_ignore_ascii_case(ans))
+            }
+            Checker::Float { absolute, relative } => compare_tokens(output, answer, |out, ans| {
+                if out == ans {
+                    return true;
+                }
+                match (out.parse::<f64>(), ans.parse::<f64>()) {
+                    (Ok(out), Ok(ans)) => {
+                        let error = (out - ans).abs();
+                        error <= *absolute || error <= relative * ans.abs()
+                    }
+                    _ => false,
+                }
+            }),
+        }
+    }
+}
+
+fn compare_tokens(output: &str, answer: &str, eq: impl Fn(&str, &str) -> bool) -> CheckResult {
+    let output: Vec<&str> = output.split_whitespace().collect();
+    let answer: Vec<&str> = answer.split_whitespace().collect();
+
+    for (index, (out, ans)) in output.iter().zip(answer.iter()).enumerate() {
+        if !eq(out, ans) {
+            return CheckResult::rejected(format!(
+                "token {} differs, expected `{}` but found `{}`",
+                index + 1,
+                ans,
+                out
+            ));
+        }
+    }
+    if output.len() != answer.len() {
+        return CheckResult::rejected(format!(
+            "expected {} tokens but found {}",
+            answer.len(),
+            output.len()
+        ));
+    }
+    CheckResult::accepted()
+}
+
+/// A testlib style checker kept at `checkers/<problem>.cpp`, or prebuilt as
+/// `checkers/<problem>`, in the workspace. It is called as
+/// `checker <input> <output> <answer>` and accepts by exiting with 0.
+pub struct ExternalChecker {
+    program: PathBuf,
+}
+
+impl ExternalChecker {
+    /// Looks for a checker for `problem_name`, compiling it when its source is
+    /// newer than the binary.
+    pub fn find(directory: &str, problem_name: &str) -> Result<Option<ExternalChecker>, String> {
+        let checkers_dir = PathBuf::from(format!("{}/checkers", directory));
+        let source = checkers_dir.join(format!("{}.cpp", problem_name));
+        let prebuilt = checkers_dir.join(format!("{}{}", problem_name, EXE_SUFFIX));
+
+        if !source.exists() {
+            return Ok(prebuilt
+                .exists()
+                .then_some(ExternalChecker { program: prebuilt }));
+        }
+
+        let binary = PathBuf::from(format!(
+            "{}/bin/checker_{}{}",
+            directory, problem_name, EXE_SUFFIX
+        ));
+        if is_stale(&binary, &source) {
+            fs::create_dir_all(binary.parent().unwrap())
+                .map_err(|err| format!("error while creating bin folder: {}", err))?;
+            let output = no_window(&mut Command::new("g++"))
+                .args(["-O2", "-std=gnu++17", "-I"])
+                .arg(&checkers_dir)
+                .arg("-o")
+                .arg(&binary)
+                .arg(&source)
+                .output()
+                .map_err(|err| format!("error while compiling checker: {}", err))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "error while compiling checker: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+        }
+
+        Ok(Some(ExternalChecker { program: binary }))
+    }
+
+    pub fn check(
+        &self,
+        input_file: &Path,
+        output_file: &Path,
+        answer_file: &Path,
+    ) -> Result<CheckResult, String> {
+        let output = no_window(&mut Command::new(&self.program))
+            .arg(input_file)
+            .arg(output_file)
+            .arg(answer_file)
+            .output()
+            .map_err(|err| format!("error while running checker: {}", err))?;
+
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let message = (!message.is_empty()).then_some(message);
+        match output.status.code() {
+            Some(0) => Ok(CheckResult {
+                accepted: true,
+                message,
+            }),
+            // testlib exits with 1 for a wrong answer and 2 for a presentation error
+            Some(1) | Some(2) => Ok(CheckResult {
+                accepted: false,
+                message,
+            }),
+            _ => Err(format!(
+                "checker failed: {}",
+                message.unwrap_or(output.status.to_string())
+            )),
+        }
+    }
+}
+
+fn is_stale(binary: &Path, source: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(binary), modified(source)) {
+        (Some(binary), Some(source)) => binary < source,
+        _ => true,
+    }
+}
+
+/// The checker a problem is judged with, an external one taking precedence.
+pub enum ProblemChecker {
+    BuiltIn(Checker),
+    External(ExternalChecker),
+}
+
+impl ProblemChecker {
+    pub fn check(
+        &self,
+        input_file: &Path,
+        output_file: &Path,
+        answer_file: &Path,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, String> {
+        match self {
+            ProblemChecker::BuiltIn(checker) => Ok(checker.check(output, answer)),
+            ProblemChecker::External(checker) => {
+                checker.check(input_file, output_file, answer_file)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOAT: Checker = Checker::Float {
+        absolute: 1e-6,
+        relative: 1e-6,
+    };
+
+    fn accepts(checker: &Checker, output: &str, answer: &str) -> bool {
+        checker.check(output, answer).accepted
+    }
+
+    #[test]
+    fn tokens_ignore_whitespace() {
+        assert!(accepts(&Checker::Tokens, "1 2  \n3", "1 2\n3\n"));
+        assert!(accepts(&Checker::Tokens, "Yes\r\n", "Yes"));
+        assert!(!accepts(&Checker::Tokens, "1 2", "1 2 3"));
+        assert!(!accepts(&Checker::Tokens, "1 2 3", "1 2"));
+        assert!(!accepts(&Checker::Tokens, "YES", "Yes"));
+    }
+
+    #[test]
+    fn token_count_is_reported() {
+        let result = Checker::Tokens.check("1 2\n3 4", "1 2\n3");
+        assert!(!result.accepted);
+        assert_eq!(
+            result.message.as_deref(),
+            Some("expected 3 tokens but found 4")
+        );
+    }
+
+    #[test]
+    fn strict_compares_bytes() {
+        assert!(accepts(&Checker::Strict, "1 2\n3", "1 2\n3"));
+        assert!(!accepts(&Checker::Strict, "1  2\n3", "1 2\n3"));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert!(accepts(&Checker::CaseInsensitive, "YES\nno", "Yes\nNo"));
+        assert!(!accepts(&Checker::CaseInsensitive, "YES", "Yes No"));
+        assert!(!accepts(&Checker::CaseInsensitive, "Yes", "Ye"));
+    }
+
+    #[test]
+    fn float_absolute_error() {
+        assert!(accepts(&FLOAT, "0.000001", "0"));
+        assert!(!accepts(&FLOAT, "0.0000011", "0"));
+        assert!(accepts(&FLOAT, "-0.000001", "0"));
+    }
+
+    #[test]
+    fn float_relative_error() {
+        assert!(accepts(&FLOAT, "1000000999", "1000000000"));
+        assert!(!accepts(&FLOAT, "1000001001", "1000000000"));
+        assert!(accepts(&FLOAT, "1.5 2", "1.5000000001 2"));
+    }
+
+    #[test]
+    fn float_rejects_nan_and_words() {
+        assert!(!accepts(&FLOAT, "nan", "1.5"));
+        assert!(!accepts(&FLOAT, "1.5", "nan"));
+        assert!(!accepts(&FLOAT, "one", "1"));
+        assert!(accepts(&FLOAT, "Yes 0.5", "Yes 0.5000001"));
+    }
+}
@@ -1,11 +1,21 @@
 use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 
+use serde::Serialize;
 use tauri::api::process::{Command, CommandEvent};
 
-use crate::judge;
-use crate::judge::Verdict;
+use crate::checker::Checker;
+use crate::custom_tests::CustomTest;
+use crate::detect::ToolchainStatus;
+use crate::judge::{
+    JudgeConfig, JudgeEvent, JudgeListener, JudgeSessions, RunOutput, StressOptions, StressResult,
+    Verdict,
+};
+use crate::language::{BuildProfile, LanguageSpec, Toolchain};
 use crate::problem::{get_problems_list, get_solved_problems, FullProblem, Problem, ProblemId};
-use crate::store::{ContestType, Language, StoreState};
+use crate::store::{ContestType, StoreState};
+use crate::{bundle, detect, judge, template};
 
 #[tauri::command]
 pub fn new_directory(directory: String, store: tauri::State<'_, StoreState>) -> Result<(), String> {
@@ -96,14 +106,22 @@ pub fn get_problem_type(store: tauri::State<'_, StoreState>) -> Result<Vec<Strin
 
 #[tauri::command]
 pub fn set_language(store: tauri::State<'_, StoreState>, language: String) -> Result<(), String> {
-    store.0.lock().unwrap().language = Language::from_str(language.as_str())
-        .map_err(|err| format!("error while setting language: {}", err))?;
+    let mut store = store.0.lock().unwrap();
+    store.language = store
+        .languages
+        .get(&language)
+        .map_err(|err| format!("error while setting language: {}", err))?
+        .id
+        .clone();
     Ok(())
 }
 
 #[tauri::command]
 pub fn get_language(store: tauri::State<'_, StoreState>) -> Result<String, ()> {
-    Ok(store.0.lock().unwrap().language.to_string().to_lowercase())
+    let store = store.0.lock().unwrap();
+    Ok(store
+        .language()
+        .map_or(store.language.clone(), |language| language.id))
 }
 
 #[tauri::command]
@@ -125,6 +143,126 @@ pub fn get_show_solved(store: tauri::State<'_, StoreState>) -> Result<bool, ()>
     Ok(store.0.lock().unwrap().show_solved)
 }
 
+#[tauri::command]
+pub fn set_judge_config(
+    store: tauri::State<'_, StoreState>,
+    judge_config: JudgeConfig,
+) -> Result<(), String> {
+    let multiplier = judge_config.time_limit_multiplier;
+    if !multiplier.is_finite() || multiplier <= 0.0 {
+        return Err(format!("invalid time limit multiplier {}", multiplier));
+    }
+    store.0.lock().unwrap().judge_config = judge_config;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_judge_config(store: tauri::State<'_, StoreState>) -> Result<JudgeConfig, ()> {
+    Ok(store.0.lock().unwrap().judge_config.clone())
+}
+
+#[tauri::command]
+pub fn set_checker(
+    store: tauri::State<'_, StoreState>,
+    checker: Option<Checker>,
+) -> Result<(), String> {
+    store.0.lock().unwrap().set_checker(checker)
+}
+
+#[tauri::command]
+pub fn get_checker(store: tauri::State<'_, StoreState>) -> Result<Checker, String> {
+    store.0.lock().unwrap().get_checker()
+}
+
+#[tauri::command]
+pub fn list_custom_tests(store: tauri::State<'_, StoreState>) -> Result<Vec<CustomTest>, String> {
+    store.0.lock().unwrap().custom_tests()?.list()
+}
+
+#[tauri::command]
+pub fn add_custom_test(
+    store: tauri::State<'_, StoreState>,
+    input: String,
+    output: Option<String>,
+) -> Result<CustomTest, String> {
+    store.0.lock().unwrap().custom_tests()?.add(input, output)
+}
+
+#[tauri::command]
+pub fn edit_custom_test(
+    store: tauri::State<'_, StoreState>,
+    test: CustomTest,
+) -> Result<(), String> {
+    store.0.lock().unwrap().custom_tests()?.edit(&test)
+}
+
+#[tauri::command]
+pub fn delete_custom_test(store: tauri::State<'_, StoreState>, name: String) -> Result<(), String> {
+    store.0.lock().unwrap().custom_tests()?.delete(&name)
+}
+
+#[tauri::command]
+pub fn get_languages(store: tauri::State<'_, StoreState>) -> Result<Vec<LanguageSpec>, ()> {
+    Ok(store.0.lock().unwrap().languages.languages().to_vec())
+}
+
+#[tauri::command]
+pub fn get_languages_error(store: tauri::State<'_, StoreState>) -> Result<Option<String>, ()> {
+    Ok(store.0.lock().unwrap().languages_error.clone())
+}
+
+#[tauri::command]
+pub async fn detect_toolchains(
+    store: tauri::State<'_, StoreState>,
+) -> Result<Vec<ToolchainStatus>, String> {
+    let languages = store.0.lock().unwrap().languages.languages().to_vec();
+    tauri::async_runtime::spawn_blocking(move || detect::detect_toolchains(&languages))
+        .await
+        .map_err(|err| format!("error while detecting toolchains: {}", err))
+}
+
+#[tauri::command]
+pub fn get_toolchain(
+    store: tauri::State<'_, StoreState>,
+    language: String,
+) -> Result<Toolchain, String> {
+    let store = store.0.lock().unwrap();
+    let language = store
+        .languages
+        .get(&language)
+        .map_err(|err| format!("error while getting toolchain: {}", err))?;
+    Ok(language.toolchain.clone())
+}
+
+#[tauri::command]
+pub fn set_toolchain(
+    store: tauri::State<'_, StoreState>,
+    language: String,
+    toolchain: Toolchain,
+) -> Result<(), String> {
+    store
+        .0
+        .lock()
+        .unwrap()
+        .languages
+        .set_toolchain(&language, toolchain)
+        .map_err(|err| format!("error while setting toolchain: {}", err))
+}
+
+#[tauri::command]
+pub fn reset_toolchain(
+    store: tauri::State<'_, StoreState>,
+    language: String,
+) -> Result<(), String> {
+    store
+        .0
+        .lock()
+        .unwrap()
+        .languages
+        .reset_toolchain(&language)
+        .map_err(|err| format!("error while resetting toolchain: {}", err))
+}
+
 #[tauri::command]
 pub fn next(store: tauri::State<'_, StoreState>) -> Result<(), String> {
     store.0.lock().unwrap().index += 1;
@@ -188,37 +326,228 @@ pub async fn update_problems_list() -> Result<(), String> {
     Ok(())
 }
 
+#[derive(Serialize, Clone)]
+struct JudgeEventPayload {
+    session: u64,
+    #[serde(flatten)]
+    event: JudgeEvent,
+}
+
+/// Forwards judge progress to the window as tauri events, tagged with the
+/// session so that the frontend can cancel it.
+fn judge_listener(window: tauri::Window, session: u64) -> JudgeListener {
+    Arc::new(move |event: JudgeEvent| {
+        let name = event.name();
+        if let Err(err) = window.emit(name, JudgeEventPayload { session, event }) {
+            println!("error while emitting judge event: {}", err);
+        }
+    })
+}
+
 #[tauri::command]
-pub async fn run(store: tauri::State<'_, StoreState>) -> Result<Vec<Verdict>, String> {
+pub async fn run(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+) -> Result<Vec<Verdict>, String> {
+    run_samples(window, store, sessions, BuildProfile::Release).await
+}
+
+/// Runs the samples built with the debug flags of the language, so that
+/// sanitizer reports show up in the verdicts.
+#[tauri::command]
+pub async fn debug_run(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+) -> Result<Vec<Verdict>, String> {
+    run_samples(window, store, sessions, BuildProfile::Debug).await
+}
+
+async fn run_samples(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+    profile: BuildProfile,
+) -> Result<Vec<Verdict>, String> {
     let mut problem = store.0.lock().unwrap().get_problem()?.clone();
     let directory = store.0.lock().unwrap().directory.clone();
-    let language = store.0.lock().unwrap().language.clone();
-    problem.scrape().await?;
-    match problem {
-        Problem::Full(problem) => judge::run(problem, directory, language).await,
-        _ => Err("error while getting full problem".into()),
+    let language = store.0.lock().unwrap().language()?;
+    let config = store.0.lock().unwrap().judge_config.clone();
+    let (session, cancel) = sessions.start();
+    let listener = judge_listener(window, session);
+    listener(JudgeEvent::Start {});
+    let res = async {
+        problem.scrape().await?;
+        match problem {
+            Problem::Full(problem) => {
+                judge::run(
+                    problem, directory, language, config, listener, cancel, profile,
+                )
+                .await
+            }
+            _ => Err("error while getting full problem".into()),
+        }
     }
+    .await;
+    sessions.finish(session);
+    res
 }
 
 #[tauri::command]
-pub async fn submit(store: tauri::State<'_, StoreState>) -> Result<Vec<Verdict>, String> {
+pub async fn submit(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+) -> Result<Vec<Verdict>, String> {
     let mut problem = store.0.lock().unwrap().get_problem()?.clone();
     let directory = store.0.lock().unwrap().directory.clone();
-    let language = store.0.lock().unwrap().language.clone();
-    problem.scrape().await?;
-    match problem {
-        Problem::Full(problem) => {
-            let res = judge::submit(problem, directory, language).await;
-            store.0.lock().unwrap().filter_problems()?;
-            res
+    let language = store.0.lock().unwrap().language()?;
+    let config = store.0.lock().unwrap().judge_config.clone();
+    let (session, cancel) = sessions.start();
+    let listener = judge_listener(window, session);
+    listener(JudgeEvent::Start {});
+    let res = async {
+        problem.scrape().await?;
+        match problem {
+            Problem::Full(problem) => {
+                let res =
+                    judge::submit(problem, directory, language, config, listener, cancel).await;
+                store.0.lock().unwrap().filter_problems()?;
+                res
+            }
+            _ => Err("error while getting full problem".into()),
         }
-        _ => Err("error while getting full problem".into()),
     }
+    .await;
+    sessions.finish(session);
+    res
 }
 
 #[tauri::command]
-pub fn create_file(store: tauri::State<'_, StoreState>) -> Result<(), String> {
-    store.0.lock().unwrap().create_file()
+pub async fn run_with_input(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+    input: String,
+) -> Result<RunOutput, String> {
+    let mut problem = store.0.lock().unwrap().get_problem()?.clone();
+    let directory = store.0.lock().unwrap().directory.clone();
+    let language = store.0.lock().unwrap().language()?;
+    let config = store.0.lock().unwrap().judge_config.clone();
+    let (session, cancel) = sessions.start();
+    let listener = judge_listener(window, session);
+    listener(JudgeEvent::Start {});
+    let res = async {
+        problem.scrape().await?;
+        match problem {
+            Problem::Full(problem) => tauri::async_runtime::spawn_blocking(move || {
+                judge::run_with_input(
+                    problem, directory, language, config, listener, cancel, input,
+                )
+            })
+            .await
+            .map_err(|err| format!("error while running: {}", err))?,
+            _ => Err("error while getting full problem".into()),
+        }
+    }
+    .await;
+    sessions.finish(session);
+    res
+}
+
+#[tauri::command]
+pub async fn stress_test(
+    window: tauri::Window,
+    store: tauri::State<'_, StoreState>,
+    sessions: tauri::State<'_, JudgeSessions>,
+    options: StressOptions,
+) -> Result<StressResult, String> {
+    let mut problem = store.0.lock().unwrap().get_problem()?.clone();
+    let directory = store.0.lock().unwrap().directory.clone();
+    let language = store.0.lock().unwrap().language()?;
+    let config = store.0.lock().unwrap().judge_config.clone();
+    let (session, cancel) = sessions.start();
+    let listener = judge_listener(window, session);
+    listener(JudgeEvent::Start {});
+    let res = async {
+        problem.scrape().await?;
+        match problem {
+            Problem::Full(problem) => tauri::async_runtime::spawn_blocking(move || {
+                judge::stress_test(
+                    problem, directory, language, config, listener, cancel, options,
+                )
+            })
+            .await
+            .map_err(|err| format!("error while stress testing: {}", err))?,
+            _ => Err("error while getting full problem".into()),
+        }
+    }
+    .await;
+    sessions.finish(session);
+    res
+}
+
+#[tauri::command]
+pub fn cancel_judge(session: u64, sessions: tauri::State<'_, JudgeSessions>) -> Result<(), String> {
+    sessions.cancel(session)
+}
+
+#[tauri::command]
+pub async fn create_file(store: tauri::State<'_, StoreState>) -> Result<(), String> {
+    let (problem, directory, language, exists) = {
+        let store = store.0.lock().unwrap();
+        (
+            store.get_problem()?,
+            store.directory.clone(),
+            store.language()?,
+            store.solution_path()?.exists(),
+        )
+    };
+    if exists {
+        return Ok(());
+    }
+    let contents = template::render(&directory, &language, problem).await?;
+    store.0.lock().unwrap().create_file(&contents)
+}
+
+/// A solution ready to be pasted into AtCoder's submit page.
+#[derive(Serialize)]
+pub struct Submission {
+    source: String,
+    task_url: String,
+    submit_url: String,
+    /// `None` when the language has no known AtCoder id
+    language_id: Option<u32>,
+}
+
+#[tauri::command]
+pub fn prepare_submission(store: tauri::State<'_, StoreState>) -> Result<Submission, String> {
+    let store = store.0.lock().unwrap();
+    let Problem::Bare(problem) = store.get_problem()? else {
+        return Err("got invalid problem while preparing submission".into());
+    };
+    let language = store.language()?;
+    let solution = store.solution_path()?;
+    if !solution.exists() {
+        return Err(format!("the file {} does not exist", solution.display()));
+    }
+
+    let directory = Path::new(&store.directory);
+    let submission = bundle::submission_path(&language, directory, &problem.file_name());
+    let source_path = if bundle::write_bundle(&language, &solution, directory, &submission)? {
+        submission
+    } else {
+        solution
+    };
+    let source = fs::read_to_string(source_path)
+        .map_err(|err| format!("error while reading solution: {}", err))?;
+    Ok(Submission {
+        source,
+        task_url: problem.url(),
+        submit_url: problem.submit_url(),
+        language_id: language.atcoder_id,
+    })
 }
 
 #[tauri::command]
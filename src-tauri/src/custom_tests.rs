@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// A test written by the user, or saved by a stress test, for one problem.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CustomTest {
+    pub name: String,
+    pub input: String,
+    /// expected output, `None` for tests the solution is only run on
+    pub output: Option<String>,
+}
+
+/// The custom tests of a problem, kept in `custom_tests/<problem>/in|out` like
+/// the downloaded ones.
+pub struct CustomTests {
+    directory: PathBuf,
+}
+
+impl CustomTests {
+    pub fn new(workspace: &str, problem_name: &str) -> CustomTests {
+        CustomTests {
+            directory: PathBuf::from(format!("{}/custom_tests/{}", workspace, problem_name)),
+        }
+    }
+
+    pub fn input_path(&self, name: &str) -> PathBuf {
+        self.directory.join("in").join(name)
+    }
+
+    pub fn answer_path(&self, name: &str) -> PathBuf {
+        self.directory.join("out").join(name)
+    }
+
+    /// Names of the tests, sorted.
+    pub fn names(&self) -> Result<Vec<String>, String> {
+        let in_dir = self.directory.join("in");
+        if !in_dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut names: Vec<String> = fs::read_dir(in_dir)
+            .map_err(|err| format!("error while reading custom tests: {}", err))?
+            .flatten()
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn list(&self) -> Result<Vec<CustomTest>, String> {
+        self.names()?
+            .into_iter()
+            .map(|name| self.get(&name))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Result<CustomTest, String> {
+        let input = fs::read_to_string(self.input_path(name))
+            .map_err(|err| format!("error while reading custom test {}: {}", name, err))?;
+        let answer_path = self.answer_path(name);
+        let output = if answer_path.exists() {
+            Some(
+                fs::read_to_string(answer_path)
+                    .map_err(|err| format!("error while reading custom test {}: {}", name, err))?,
+            )
+        } else {
+            None
+        };
+        Ok(CustomTest {
+            name: name.into(),
+            input,
+            output,
+        })
+    }
+
+    /// Adds a test under the first free `custom_<n>` name.
+    pub fn add(&self, input: String, output: Option<String>) -> Result<CustomTest, String> {
+        let names = self.names()?;
+        let name = (1..)
+            .map(|n| format!("custom_{}", n))
+            .find(|name| !names.contains(name))
+            .unwrap();
+        let test = CustomTest {
+            name,
+            input,
+            output,
+        };
+        self.save(&test)?;
+        Ok(test)
+    }
+
+    pub fn edit(&self, test: &CustomTest) -> Result<(), String> {
+        check_name(&test.name)?;
+        if !self.input_path(&test.name).exists() {
+            return Err(format!("no custom test named {}", test.name));
+        }
+        self.save(test)
+    }
+
+    /// Writes a test, replacing the one with the same name.
+    pub fn save(&self, test: &CustomTest) -> Result<(), String> {
+        check_name(&test.name)?;
+        for folder in ["in", "out"] {
+            fs::create_dir_all(self.directory.join(folder))
+                .map_err(|err| format!("error while creating custom tests folder: {}", err))?;
+        }
+        fs::write(self.input_path(&test.name), &test.input)
+            .map_err(|err| format!("error while writing custom test: {}", err))?;
+        let answer_path = self.answer_path(&test.name);
+        match &test.output {
+            Some(output) => fs::write(answer_path, output)
+                .map_err(|err| format!("error while writing custom test: {}", err)),
+            None => remove_if_exists(&answer_path),
+        }
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        check_name(name)?;
+        if !self.input_path(name).exists() {
+            return Err(format!("no custom test named {}", name));
+        }
+        remove_if_exists(&self.input_path(name))?;
+        remove_if_exists(&self.answer_path(name))
+    }
+}
+
+/// Keeps names to a single file inside the tests folder.
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(format!("invalid custom test name {}", name));
+    }
+    Ok(())
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_file(path).map_err(|err| format!("error while deleting custom test: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests of a problem in a fresh workspace under the temp dir.
+    fn custom_tests(workspace: &str) -> CustomTests {
+        let workspace = std::env::temp_dir().join(format!(
+            "xcoder_custom_tests_{}_{}",
+            workspace,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&workspace);
+        CustomTests::new(workspace.to_str().unwrap(), "ABC123_A")
+    }
+
+    #[test]
+    fn check_name_keeps_tests_in_the_folder() {
+        assert!(check_name("custom_1").is_ok());
+        assert!(check_name("stress_42").is_ok());
+        for name in ["", ".", "..", "../escape", "a/b", "a\\b"] {
+            assert!(check_name(name).is_err(), "{:?} was accepted", name);
+        }
+    }
+
+    #[test]
+    fn add_takes_the_first_free_number() {
+        let tests = custom_tests("numbering");
+        assert_eq!(tests.add("1".into(), None).unwrap().name, "custom_1");
+        assert_eq!(
+            tests.add("2".into(), Some("2".into())).unwrap().name,
+            "custom_2"
+        );
+        assert_eq!(tests.add("3".into(), None).unwrap().name, "custom_3");
+
+        tests.delete("custom_2").unwrap();
+        assert_eq!(tests.add("4".into(), None).unwrap().name, "custom_2");
+        assert_eq!(tests.add("5".into(), None).unwrap().name, "custom_4");
+        assert_eq!(
+            tests.names().unwrap(),
+            ["custom_1", "custom_2", "custom_3", "custom_4"]
+        );
+    }
+}
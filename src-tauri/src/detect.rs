@@ -0,0 +1,163 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::language::LanguageSpec;
+use crate::runner::{no_window, read_pipe};
+
+/// Whether the program a language needs is installed, and which version.
+#[derive(Serialize, Clone)]
+pub struct ToolchainStatus {
+    pub language: String,
+    pub program: Option<String>,
+    /// where the program was found on `PATH`
+    pub path: Option<PathBuf>,
+    /// first line the program printed for its version
+    pub version: Option<String>,
+    /// why the language cannot be used, `None` when it can
+    pub error: Option<String>,
+}
+
+/// Checks every language at once, as some compilers take a while to start.
+pub fn detect_toolchains(languages: &[LanguageSpec]) -> Vec<ToolchainStatus> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = languages
+            .iter()
+            .map(|language| scope.spawn(|| detect(language)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn detect(language: &LanguageSpec) -> ToolchainStatus {
+    let mut status = ToolchainStatus {
+        language: language.id.clone(),
+        program: None,
+        path: None,
+        version: None,
+        error: None,
+    };
+
+    let Some(program) = language.program() else {
+        status.error = Some(format!("no command is configured for {}", language.name));
+        return status;
+    };
+    status.program = Some(program.to_string());
+
+    let Some(path) = find_program(program) else {
+        status.error = Some(format!("{} was not found on PATH", program));
+        return status;
+    };
+    status.path = Some(path);
+
+    match read_version(&language.version_command()) {
+        Ok(version) => {
+            status.version = version
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(String::from)
+        }
+        Err(err) => status.error = Some(err),
+    }
+    status
+}
+
+/// Longest a version command may take, a compiler that hangs would otherwise
+/// hold up detection and every compile.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Runs a version command, returning everything it printed.
+pub fn read_version(command: &[String]) -> Result<String, String> {
+    let (program, args) = command
+        .split_first()
+        .ok_or("error while reading version: empty command".to_string())?;
+    let mut child = no_window(&mut Command::new(program))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("error while reading version of {}: {}", program, err))?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "error while reading version of {}: no answer after {} seconds",
+                    program,
+                    VERSION_TIMEOUT.as_secs()
+                ));
+            }
+            Err(err) => {
+                return Err(format!(
+                    "error while reading version of {}: {}",
+                    program, err
+                ))
+            }
+        }
+    }
+    let mut version = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).to_string();
+    version.push_str(&String::from_utf8_lossy(&stderr.join().unwrap_or_default()));
+    Ok(version)
+}
+
+/// Resolves a program the way spawning it would, through `PATH` unless it is
+/// given as a path.
+fn find_program(program: &str) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return is_executable(path).then(|| path.to_path_buf());
+    }
+
+    let extensions = executable_extensions();
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|extension| dir.join(format!("{}{}", program, extension)))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+#[cfg(windows)]
+fn executable_extensions() -> Vec<String> {
+    let mut extensions = vec![String::new()];
+    extensions.extend(
+        env::var("PATHEXT")
+            .unwrap_or(".EXE;.CMD;.BAT".into())
+            .split(';')
+            .map(|extension| extension.to_lowercase()),
+    );
+    extensions
+}
+
+#[cfg(not(windows))]
+fn executable_extensions() -> Vec<String> {
+    vec![String::new()]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file())
+}
@@ -0,0 +1,398 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CompileResult {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: u32,
+    pub column: Option<u32>,
+    pub severity: String,
+    pub message: String,
+}
+
+impl CompileResult {
+    /// Result when nothing had to be built, for languages that are run
+    /// straight from source and for binaries that are still up to date.
+    pub fn skipped() -> CompileResult {
+        CompileResult {
+            success: true,
+            exit_code: None,
+            stderr: String::new(),
+            diagnostics: vec![],
+        }
+    }
+
+    /// Result of a build that was killed because the run was cancelled.
+    pub fn cancelled() -> CompileResult {
+        CompileResult {
+            success: false,
+            exit_code: None,
+            stderr: "cancelled".into(),
+            diagnostics: vec![],
+        }
+    }
+
+    pub fn new(exit_code: Option<i32>, stdout: &[u8], stderr: &[u8]) -> CompileResult {
+        let stdout = String::from_utf8_lossy(stdout);
+        let mut stderr = String::from_utf8_lossy(stderr).to_string();
+        // some compilers report their errors on stdout
+        if stderr.trim().is_empty() {
+            stderr = stdout.to_string();
+        }
+        CompileResult {
+            success: exit_code == Some(0),
+            exit_code,
+            diagnostics: parse_diagnostics(&stderr),
+            stderr,
+        }
+    }
+}
+
+/// Picks file, line, column and message out of compiler output. Understands
+/// the `file:line:col: severity: message` format of gcc, clang, go, zig,
+/// swiftc, ghc and gfortran, the `--> file:line:col` notes of rustc and the
+/// `File "file", line n` headers of ocaml.
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = output.lines().collect();
+    let mut diagnostics = vec![];
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if let Some((severity, message)) = rustc_header(line) {
+            if let Some((file, line, column, _)) = lines
+                .get(i)
+                .and_then(|next| next.trim_start().strip_prefix("--> "))
+                .and_then(split_location)
+            {
+                diagnostics.push(Diagnostic {
+                    file,
+                    line,
+                    column,
+                    severity,
+                    message,
+                });
+                i += 1;
+            }
+            continue;
+        }
+
+        if let Some((file, line, column)) = ocaml_location(line) {
+            // newer versions quote the source before the message
+            while lines
+                .get(i)
+                .is_some_and(|next| next.contains('|') || next.trim().starts_with('^'))
+            {
+                i += 1;
+            }
+            if let Some((severity, message)) = lines.get(i).and_then(|next| severity_prefix(next)) {
+                diagnostics.push(Diagnostic {
+                    file,
+                    line,
+                    column,
+                    severity,
+                    message,
+                });
+                i += 1;
+            }
+            continue;
+        }
+
+        if let Some((file, line, column, rest)) = split_location(line) {
+            let (mut severity, mut message) =
+                severity_prefix(&rest).unwrap_or(("error".into(), rest.trim().to_string()));
+            // ghc and gfortran put the message on the lines that follow, after
+            // an error code or a snippet of the source
+            if message.is_empty() || message.starts_with('[') {
+                while let Some(next) = lines.get(i) {
+                    if split_location(next).is_some() {
+                        break;
+                    }
+                    i += 1;
+                    if let Some(found) = severity_prefix(next) {
+                        (severity, message) = found;
+                        break;
+                    }
+                    if next.starts_with(char::is_whitespace)
+                        && !next.trim().is_empty()
+                        && !next.contains('|')
+                    {
+                        message = next.trim().to_string();
+                        break;
+                    }
+                }
+            }
+            diagnostics.push(Diagnostic {
+                file,
+                line,
+                column,
+                severity,
+                message,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Splits `file:line:col: rest` or `file:line: rest`, skipping over the
+/// colon of a windows drive letter.
+fn split_location(text: &str) -> Option<(String, u32, Option<u32>, String)> {
+    let text = text.trim();
+    // the include chain gcc prints before an error in a header
+    if text.starts_with("In file included from ") || text.starts_with("from ") {
+        return None;
+    }
+    for (index, _) in text.match_indices(':') {
+        let (file, rest) = text.split_at(index);
+        if !is_file_name(file) {
+            continue;
+        }
+        let mut parts = rest[1..].splitn(3, ':');
+        let Some(Ok(line)) = parts.next().map(str::parse::<u32>) else {
+            continue;
+        };
+        let second = parts.next();
+        let third = parts.next();
+        return Some(match (second.map(str::parse::<u32>), third) {
+            (Some(Ok(column)), rest) => (
+                file.to_string(),
+                line,
+                Some(column),
+                rest.unwrap_or("").to_string(),
+            ),
+            (_, _) => (
+                file.to_string(),
+                line,
+                None,
+                [second, third]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(":"),
+            ),
+        });
+    }
+    None
+}
+
+/// Paths may contain spaces, as in `C:\Program Files\...`, but not before
+/// their first separator, text like that is a sentence.
+fn is_file_name(text: &str) -> bool {
+    if text.is_empty() {
+        return false;
+    }
+    match (text.find(char::is_whitespace), text.find(['/', '\\'])) {
+        (None, _) => true,
+        (Some(space), Some(separator)) => separator < space,
+        (Some(_), None) => false,
+    }
+}
+
+fn severity_prefix(text: &str) -> Option<(String, String)> {
+    let text = text.trim();
+    for severity in ["fatal error", "error", "warning", "note"] {
+        if let Some(rest) = text
+            .strip_prefix(severity)
+            .or_else(|| text.strip_prefix(&capitalize(severity)))
+        {
+            // `Warning 26 [unused-var]: ...` in ocaml, `error[E0425]: ...` in rustc
+            if let Some((_, message)) = rest.split_once(':') {
+                return Some((severity.to_string(), message.trim().to_string()));
+            }
+        }
+    }
+    None
+}
+
+fn rustc_header(line: &str) -> Option<(String, String)> {
+    if !line.starts_with("error") && !line.starts_with("warning") {
+        return None;
+    }
+    let (severity, message) = severity_prefix(line)?;
+    // the summary lines at the end have no location
+    if message.starts_with("aborting due to") {
+        return None;
+    }
+    Some((severity, message))
+}
+
+fn ocaml_location(line: &str) -> Option<(String, u32, Option<u32>)> {
+    let rest = line.strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once("\", line ")?;
+    let line_end = rest.find(|c: char| !c.is_ascii_digit())?;
+    let number = rest[..line_end].parse().ok()?;
+    let column = rest[line_end..]
+        .strip_prefix(", characters ")
+        .and_then(|chars| chars.split('-').next())
+        .and_then(|start| start.parse::<u32>().ok())
+        .map(|start| start + 1);
+    Some((file.to_string(), number, column))
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(
+        file: &str,
+        line: u32,
+        column: Option<u32>,
+        severity: &str,
+        message: &str,
+    ) -> Diagnostic {
+        Diagnostic {
+            file: file.into(),
+            line,
+            column,
+            severity: severity.into(),
+            message: message.into(),
+        }
+    }
+
+    #[test]
+    fn gcc() {
+        let output = "\
+In file included from /home/u/ws/mid.hpp:1,
+                 from /home/u/ws/abc.cpp:1:
+/home/u/ws/lib.hpp: In function 'int f()':
+/home/u/ws/lib.hpp:2:18: error: 'undefined_name' was not declared in this scope
+    2 | int f() { return undefined_name; }
+      |                  ^~~~~~~~~~~~~~
+/home/u/ws/abc.cpp: In function 'int main()':
+/home/u/ws/abc.cpp:2:24: error: expected ',' or ';' before '}' token
+    2 | int main() { int x = 1 }
+      |                        ^
+/home/u/ws/abc.cpp:3:9: warning: unused variable 'y' [-Wunused-variable]
+";
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![
+                diagnostic(
+                    "/home/u/ws/lib.hpp",
+                    2,
+                    Some(18),
+                    "error",
+                    "'undefined_name' was not declared in this scope",
+                ),
+                diagnostic(
+                    "/home/u/ws/abc.cpp",
+                    2,
+                    Some(24),
+                    "error",
+                    "expected ',' or ';' before '}' token",
+                ),
+                diagnostic(
+                    "/home/u/ws/abc.cpp",
+                    3,
+                    Some(9),
+                    "warning",
+                    "unused variable 'y' [-Wunused-variable]",
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn gcc_windows_path() {
+        let output =
+            "C:\\Users\\My Name\\ws\\abc.cpp:4:5: error: 'foo' was not declared in this scope\n";
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![diagnostic(
+                "C:\\Users\\My Name\\ws\\abc.cpp",
+                4,
+                Some(5),
+                "error",
+                "'foo' was not declared in this scope",
+            )]
+        );
+    }
+
+    #[test]
+    fn rustc() {
+        let output = "\
+error[E0308]: mismatched types
+ --> /home/u/ws/abc.rs:2:18
+  |
+2 |     let x: i32 = \"a\";
+  |            ---   ^^^ expected `i32`, found `&str`
+  |            |
+  |            expected due to this
+
+error: aborting due to 1 previous error
+
+For more information about this error, try `rustc --explain E0308`.
+";
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![diagnostic(
+                "/home/u/ws/abc.rs",
+                2,
+                Some(18),
+                "error",
+                "mismatched types",
+            )]
+        );
+    }
+
+    #[test]
+    fn ghc() {
+        let output = "\
+[1 of 2] Compiling Main             ( /home/u/ws/abc.hs, /home/u/ws/abc.o )
+
+/home/u/ws/abc.hs:3:8: error: [GHC-88464]
+    Variable not in scope: foo :: IO ()
+  |
+3 | main = foo
+  |        ^^^
+";
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![diagnostic(
+                "/home/u/ws/abc.hs",
+                3,
+                Some(8),
+                "error",
+                "Variable not in scope: foo :: IO ()",
+            )]
+        );
+    }
+
+    #[test]
+    fn ocaml() {
+        let output = "\
+File \"/home/u/ws/abc.ml\", line 2, characters 8-11:
+2 | let x = foo
+            ^^^
+Error: Unbound value foo
+";
+        assert_eq!(
+            parse_diagnostics(output),
+            vec![diagnostic(
+                "/home/u/ws/abc.ml",
+                2,
+                Some(9),
+                "error",
+                "Unbound value foo",
+            )]
+        );
+    }
+}
@@ -1,17 +1,27 @@
 use std::collections::HashMap;
-use std::io::{Cursor, Write};
-use std::os::windows::process::CommandExt;
+use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::{fs, io};
+use std::{fs, io, thread};
 
 use serde::{Deserialize, Serialize};
-use wait_timeout::ChildExt;
 use zip::ZipArchive;
 
+use crate::bundle;
+use crate::cache;
+use crate::checker::{Checker, ExternalChecker, ProblemChecker};
+use crate::custom_tests::{CustomTest, CustomTests};
+use crate::diagnostics::CompileResult;
+use crate::language::{BuildProfile, LanguageSpec};
 use crate::problem::*;
-use crate::store::{LangType, Language};
+use crate::project::Project;
+use crate::runner::{
+    exit_reason, native_runner, no_window, output_unless_cancelled, CancelToken, RunLimits,
+    RunPlan, Runner, Termination,
+};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Verdict {
@@ -20,10 +30,25 @@ pub struct Verdict {
     answer: String,
     status: Option<JudgeStatus>,
     time: Option<f32>,
+    cpu_time: Option<f32>,
     memory: Option<u64>,
+    compile_result: Option<CompileResult>,
+    checker_message: Option<String>,
+    /// what the solution printed to stderr, cut to `STDERR_LIMIT` bytes
+    stderr: Option<String>,
+    exit_code: Option<i32>,
+    /// signal that killed the solution, like `SIGSEGV`
+    signal: Option<String>,
+    /// why the solution got RE
+    re_reason: Option<String>,
 }
 
+/// Largest part of stderr kept in a verdict, in bytes. Sanitizers put the
+/// cause of the error first.
+const STDERR_LIMIT: u64 = 64 * 1024;
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum JudgeStatus {
     CE,
     MLE,
@@ -33,6 +58,177 @@ pub enum JudgeStatus {
     IE,
     WA,
     AC,
+    /// ran without errors on a test that has no expected output
+    OK,
+    /// not run because the judge run was cancelled
+    SKIP,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct JudgeConfig {
+    /// largest output a solution may print, in megabytes
+    pub output_limit: u64,
+    /// run solutions in the linux sandbox, see `sandbox::Sandbox`
+    pub sandbox: bool,
+    /// scales the time limit of the problem, for machines slower than atcoder's
+    pub time_limit_multiplier: f64,
+    /// built-in checker for problems that have none of their own
+    pub default_checker: Checker,
+    /// built-in checker to use per problem, keyed by the problem's file name
+    pub checkers: HashMap<String, Checker>,
+    /// how many test cases are run at the same time, capped at the core count
+    pub workers: usize,
+}
+
+impl JudgeConfig {
+    pub fn checker(&self, problem_name: &str) -> Checker {
+        self.checkers
+            .get(problem_name)
+            .unwrap_or(&self.default_checker)
+            .clone()
+    }
+}
+
+impl Default for JudgeConfig {
+    fn default() -> JudgeConfig {
+        JudgeConfig {
+            output_limit: 64,
+            sandbox: false,
+            time_limit_multiplier: 1.0,
+            default_checker: Checker::default(),
+            checkers: HashMap::new(),
+            workers: 4,
+        }
+    }
+}
+
+/// Progress reported while judging, emitted to the frontend as tauri events.
+#[derive(Serialize, Clone)]
+#[serde(untagged)]
+pub enum JudgeEvent {
+    Start {},
+    CompileStart {},
+    CompileEnd {
+        result: CompileResult,
+    },
+    Verdict {
+        index: usize,
+        total: usize,
+        name: String,
+        verdict: Box<Verdict>,
+    },
+}
+
+impl JudgeEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JudgeEvent::Start {} => "judge-start",
+            JudgeEvent::CompileStart {} => "judge-compile-start",
+            JudgeEvent::CompileEnd { .. } => "judge-compile-end",
+            JudgeEvent::Verdict { .. } => "judge-verdict",
+        }
+    }
+}
+
+pub type JudgeListener = Arc<dyn Fn(JudgeEvent) + Send + Sync>;
+
+/// Judge runs in progress, so that they can be cancelled by their id.
+#[derive(Default)]
+pub struct JudgeSessions {
+    next_id: AtomicU64,
+    sessions: Mutex<HashMap<u64, CancelToken>>,
+}
+
+impl JudgeSessions {
+    pub fn start(&self) -> (u64, CancelToken) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let cancel = CancelToken::default();
+        self.sessions.lock().unwrap().insert(id, cancel.clone());
+        (id, cancel)
+    }
+
+    pub fn finish(&self, id: u64) {
+        self.sessions.lock().unwrap().remove(&id);
+    }
+
+    pub fn cancel(&self, id: u64) -> Result<(), String> {
+        match self.sessions.lock().unwrap().get(&id) {
+            Some(cancel) => {
+                cancel.cancel();
+                Ok(())
+            }
+            None => Err(format!("error while cancelling judge: no session {}", id)),
+        }
+    }
+}
+
+/// Cpu time the generator and the brute force solution of a stress test may
+/// take per input, brute force being slow by design.
+const HELPER_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+pub struct StressOptions {
+    /// most inputs to try
+    pub iterations: u64,
+    /// seconds after which no new input is tried
+    pub time_budget: f64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct StressResult {
+    /// how many inputs the solution was run on
+    iterations: u64,
+    /// seed of the input the solution failed on, `None` when it never failed
+    seed: Option<u64>,
+    /// verdict on the failing input, or the compile error of the solution
+    verdict: Option<Verdict>,
+    /// name of the custom test the failing input was saved as
+    test_name: Option<String>,
+}
+
+/// What the solution printed for input typed in by the user.
+#[derive(Serialize, Clone)]
+pub struct RunOutput {
+    /// `CE` when the solution did not build, `OK` when it exited successfully
+    status: JudgeStatus,
+    stdout: String,
+    /// cut to `STDERR_LIMIT` bytes
+    stderr: String,
+    /// `None` when the solution did not exit by itself
+    exit_code: Option<i32>,
+    time: Option<f32>,
+    cpu_time: Option<f32>,
+    memory: Option<u64>,
+    compile_result: Option<CompileResult>,
+}
+
+pub struct TestFiles {
+    pub name: String,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    /// where stderr is written
+    pub error: PathBuf,
+    /// `None` for tests the solution is only run on
+    pub answer: Option<PathBuf>,
+}
+
+/// Reads at most `STDERR_LIMIT` bytes of stderr, `None` when it is empty.
+fn read_stderr(error_file: &Path) -> Result<Option<String>, String> {
+    let mut bytes = vec![];
+    fs::File::open(error_file)
+        .and_then(|file| file.take(STDERR_LIMIT + 1).read_to_end(&mut bytes))
+        .map_err(|err| format!("error while reading stderr: {}", err))?;
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let truncated = bytes.len() as u64 > STDERR_LIMIT;
+    bytes.truncate(STDERR_LIMIT as usize);
+    let mut stderr = String::from_utf8_lossy(&bytes).to_string();
+    if truncated {
+        stderr.push_str("\n... (truncated)");
+    }
+    Ok(Some(stderr))
 }
 
 impl Verdict {
@@ -43,67 +239,119 @@ impl Verdict {
             answer,
             status: None,
             time: None,
+            cpu_time: None,
             memory: None,
+            compile_result: None,
+            checker_message: None,
+            stderr: None,
+            exit_code: None,
+            signal: None,
+            re_reason: None,
         }
     }
 
+    fn skipped(mut self) -> Verdict {
+        self.status = Some(JudgeStatus::SKIP);
+        self
+    }
+
+    fn compile_error(result: CompileResult) -> Verdict {
+        let mut verdict = Verdict::new(String::new(), String::new());
+        verdict.output = Some(result.stderr.clone());
+        verdict.status = Some(JudgeStatus::CE);
+        verdict.compile_result = Some(result);
+        verdict
+    }
+
     pub fn exec(
         &mut self,
-        binary_path: String,
-        input_file: PathBuf,
-        output_file: PathBuf,
-        timeout: Duration,
+        runner: &dyn Runner,
+        plan: &RunPlan,
+        files: &TestFiles,
+        checker: &ProblemChecker,
+        limits: &RunLimits,
     ) -> Result<(), String> {
-        let mut sol_process = Command::new("powershell")
-            .current_dir(input_file.parent().unwrap())
-            .args(["-Command", "-"])
-            .stdin(Stdio::piped())
-            .spawn()
-            .map_err(|err| format!("error while running solution: {}", err))?;
-
-        sol_process
-            .stdin
-            .take()
-            .ok_or(format!("error while taking stdin of powershell"))?
-            .write_fmt(format_args!(
-                "{}",
-                format!("Start-Process '{}' -RedirectStandardInput '{}' -RedirectStandardOutput '{}' -NoNewWindow -Wait", binary_path, input_file.display(), output_file.display())
-                    .replace("/", "\\")
-                    .as_str()
-            ))
-            .map_err(|err| format!("error while giving command to powershell: {}", err))?;
-        let now = Instant::now();
-
-        match sol_process.wait_timeout(timeout).unwrap() {
-            Some(x) => {
+        let result = runner.run(
+            plan,
+            &files.input,
+            &files.output,
+            Some(&files.error),
+            limits,
+        )?;
+        self.time = Some(result.time.as_secs_f32());
+        self.cpu_time = result.cpu_time.map(|t| t.as_secs_f32());
+        self.memory = result.memory;
+        self.stderr = read_stderr(&files.error)?;
+
+        match result.termination {
+            Termination::Exited(x) => {
+                self.exit_code = x.code();
                 if x.success() {
-                    self.time = Some(now.elapsed().as_secs_f32());
                     self.output = Some(
-                        fs::read_to_string(output_file)
+                        fs::read_to_string(&files.output)
                             .map_err(|err| {
                                 format!("error while reading output from file: {}", err)
                             })?
                             .trim()
-                            .to_string(),
+                            .replace("\r\n", "\n"),
                     );
-                    if self.answer.eq(self.output.as_ref().unwrap()) {
-                        self.status = Some(JudgeStatus::AC);
+                    if let Some(answer) = &files.answer {
+                        let check = checker.check(
+                            &files.input,
+                            &files.output,
+                            answer,
+                            self.output.as_ref().unwrap(),
+                            &self.answer,
+                        )?;
+                        self.checker_message = check.message;
+                        if check.accepted {
+                            self.status = Some(JudgeStatus::AC);
+                        } else {
+                            self.status = Some(JudgeStatus::WA);
+                        }
                     } else {
-                        self.status = Some(JudgeStatus::WA);
-                    }
-                    if self.time.as_ref().unwrap() > &(timeout.as_secs_f32() - 2.0) {
-                        self.status = Some(JudgeStatus::TLE);
+                        self.status = Some(JudgeStatus::OK);
                     }
                 } else {
+                    let reason = exit_reason(&x);
+                    self.signal = reason.signal;
+                    self.re_reason = Some(reason.description);
                     self.status = Some(JudgeStatus::RE);
                 }
             }
-            None => {
+            Termination::TimedOut => {
                 self.status = Some(JudgeStatus::TLE);
             }
+            Termination::MemoryLimitExceeded => {
+                self.status = Some(JudgeStatus::MLE);
+            }
+            Termination::OutputLimitExceeded => {
+                self.status = Some(JudgeStatus::OLE);
+            }
+            Termination::Cancelled => {
+                self.status = Some(JudgeStatus::SKIP);
+                return Ok(());
+            }
         };
 
-        // TODO: check status, and memory
+        // a solution that ran over the time limit is TLE however it exited,
+        // falling back to wall time where cpu time is not measured
+        if let Some(JudgeStatus::AC | JudgeStatus::WA | JudgeStatus::OK | JudgeStatus::RE) =
+            self.status
+        {
+            if result.cpu_time.unwrap_or(result.time) > limits.time_limit {
+                self.status = Some(JudgeStatus::TLE);
+            }
+        }
+
+        // the kill in the runner is not instant, so a solution can still finish
+        // between two polls after going over the limit
+        if let (Some(memory), Some(limit)) = (self.memory, limits.memory_limit) {
+            if memory > limit {
+                self.status = Some(JudgeStatus::MLE);
+            }
+        }
+
         Ok(())
     }
 }
@@ -111,42 +359,127 @@ impl Verdict {
 pub struct Judge {
     problem: FullProblem,
     directory: String,
-    language: Language,
-    binary_path: Option<String>,
+    language: LanguageSpec,
+    config: JudgeConfig,
+    plan: Option<RunPlan>,
+    runner: Box<dyn Runner>,
+    listener: JudgeListener,
+    cancel: CancelToken,
+    profile: BuildProfile,
 }
 
 impl Judge {
-    fn new(problem: FullProblem, directory: String, language: Language) -> Judge {
-        Judge {
+    fn new(
+        problem: FullProblem,
+        directory: String,
+        language: LanguageSpec,
+        config: JudgeConfig,
+        listener: JudgeListener,
+        cancel: CancelToken,
+        profile: BuildProfile,
+    ) -> Result<Judge, String> {
+        let runner = native_runner(
+            config.sandbox.then_some(Path::new(&directory)),
+            config.workers,
+            cancel.clone(),
+        )?;
+        Ok(Judge {
             problem,
             directory,
             language,
-            binary_path: None,
-        }
+            config,
+            plan: None,
+            runner,
+            listener,
+            cancel,
+            profile,
+        })
     }
 
-    pub fn compile(&mut self) -> Result<(), String> {
+    /// Builds the solution, returning `Err` only when the compiler could not be
+    /// run at all. A failed build comes back as an unsuccessful `CompileResult`.
+    pub fn compile(&mut self) -> Result<CompileResult, String> {
+        let name = self.problem.file_name();
+        // the bundle is what gets submitted, so it is what gets judged, unless
+        // a project tool builds the solution from its own sources
+        let bundle_path =
+            bundle::submission_path(&self.language, Path::new(&self.directory), &name);
+        let (result, plan) = self.compile_program(&name, &bundle_path)?;
+        self.plan = plan;
+        Ok(result)
+    }
+
+    /// Builds the program kept in `<name>.<ext>` next to the solution,
+    /// returning how to run it when the build succeeded. Sources that pull in
+    /// local files are built from their bundle, written to `bundle_path`.
+    /// Projects are built by their own tool, from the original sources.
+    /// Debug builds get a binary of their own, so that they do not invalidate
+    /// the cached release one.
+    fn compile_program(
+        &self,
+        name: &str,
+        bundle_path: &Path,
+    ) -> Result<(CompileResult, Option<RunPlan>), String> {
         let mut file_path = PathBuf::from(format!(
-            "{}/{}/{}{}_{}",
-            self.directory,
-            self.language.source_directory(),
-            self.problem.contest_type.to_string().to_lowercase(),
-            self.problem.contest_id,
-            self.problem.problem_id,
+            "{}/{}/{}",
+            self.directory, self.language.source_directory, name
         ));
-        file_path.set_extension(self.language.extension());
+        file_path.set_extension(&self.language.extension);
 
         if !file_path.exists() {
             return Err(format!("the file {} does not exist", file_path.display()));
         }
 
-        if self.language.lang_type() == LangType::Interpreted {
-            self.binary_path = Some(format!(
-                "{} '{}'",
-                self.language.interpreter(),
-                file_path.display()
-            ));
-            return Ok(());
+        let directory = Path::new(&self.directory);
+        if let Some(project) = Project::detect(&self.language, directory) {
+            let env = &self.language.toolchain.env;
+            let Some(output) = output_unless_cancelled(
+                project
+                    .build_command(directory, name, self.profile)
+                    .envs(env),
+                &self.cancel,
+            )
+            .map_err(|err| format!("error while compiling: {}", err))?
+            else {
+                return Ok((CompileResult::cancelled(), None));
+            };
+            let result = CompileResult::new(output.status.code(), &output.stdout, &output.stderr);
+            let plan = if result.success {
+                Some(project.run_plan(directory, name, env, self.profile)?)
+            } else {
+                None
+            };
+            return Ok((result, plan));
+        }
+
+        let bundled = bundle::write_bundle(&self.language, &file_path, directory, bundle_path)?;
+        let binary_name = match self.profile {
+            BuildProfile::Release => name.to_string(),
+            BuildProfile::Debug => format!("{}_debug", name),
+        };
+        let binary_path = PathBuf::from(format!(
+            "{}/bin/{}",
+            self.directory,
+            self.language.binary_file_name(&binary_name)
+        ));
+        let file_path = if bundled {
+            bundle_path.to_path_buf()
+        } else {
+            file_path
+        };
+        let toolchain = &self.language.toolchain;
+        let mut plan =
+            self.language
+                .run_plan(&file_path, &binary_path, Path::new(&self.directory))?;
+        if self.profile == BuildProfile::Debug {
+            // solutions seldom free what they allocate, which is not worth an RE
+            plan.env
+                .entry("ASAN_OPTIONS".into())
+                .or_insert("detect_leaks=0".into());
+        }
+
+        if !self.language.compiled {
+            return Ok((CompileResult::skipped(), Some(plan)));
         }
 
         if !Path::new(&format!("{}/bin", self.directory)).exists() {
@@ -154,65 +487,74 @@ impl Judge {
                 .map_err(|err| format!("error while creating bin folder: {}", err))?;
         }
 
-        let binary_path = PathBuf::from(format!(
-            "{}/bin/{}{}_{}.exe",
-            self.directory,
-            self.problem.contest_type.to_string().to_lowercase(),
-            self.problem.contest_id,
-            self.problem.problem_id
-        ));
+        let compile = self.language.expand(
+            self.language.compile_command(self.profile),
+            &file_path,
+            &binary_path,
+        )?;
+        let mut compiler = Command::new(&compile[0]);
+        compiler.args(&compile[1..]).envs(&toolchain.env);
+        let key = cache::compile_key(&self.language, &compiler, &file_path)?;
+        if cache::is_fresh(&binary_path, &key) {
+            return Ok((CompileResult::skipped(), Some(plan)));
+        }
 
-        let output = self
-            .language
-            .compiler()
-            .current_dir(&self.directory)
-            .arg("-o")
-            .arg(binary_path.clone())
-            .arg(file_path)
-            .creation_flags(0x08000000)
-            .output()
-            .map_err(|err| format!("error while compiling: {}", err))?;
-        if output.status.success() {
-            self.binary_path = Some(binary_path.to_str().unwrap().into());
-            Ok(())
-        } else {
-            Err(format!(
-                "error while compiling: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
+        cache::remove_key(&binary_path);
+        let Some(output) = output_unless_cancelled(
+            no_window(&mut compiler).current_dir(&self.directory),
+            &self.cancel,
+        )
+        .map_err(|err| format!("error while compiling: {}", err))?
+        else {
+            return Ok((CompileResult::cancelled(), None));
+        };
+        let result = CompileResult::new(output.status.code(), &output.stdout, &output.stderr);
+        if !result.success {
+            return Ok((result, None));
         }
+        cache::save_key(&binary_path, &key)?;
+        Ok((result, Some(plan)))
     }
 
-    pub async fn download_test_cases(&self) -> Result<(), String> {
+    /// Downloads the tests of the problem unless they are already there,
+    /// returning `false` when the run was cancelled during the download.
+    pub async fn download_test_cases(&self) -> Result<bool, String> {
         if !Path::new(&format!("{}/test_cases", self.directory)).exists() {
             fs::create_dir(&format!("{}/test_cases", self.directory))
                 .map_err(|err| format!("error while creating test_cases folder: {}", err))?;
         }
 
         let test_cases_path = PathBuf::from(format!(
-            "{}/test_cases/{}{}_{}",
+            "{}/test_cases/{}",
             self.directory,
-            self.problem.contest_type,
-            self.problem.contest_id,
-            self.problem.problem_id
+            self.problem.test_cases_name()
         ));
 
         if test_cases_path.exists() {
-            return Ok(());
+            return Ok(true);
         }
 
         let mut link = self.problem.test_cases_link.clone().trim().to_string();
         link.pop();
         link.push('1');
-        let mut archive = ZipArchive::new(Cursor::new(
-            reqwest::get(link)
-                .await
-                .map_err(|err| format!("error while getting test cases response: {}", err))?
-                .bytes()
-                .await
-                .map_err(|err| format!("error while getting test cases bytes: {}", err))?,
-        ))
-        .map_err(|err| format!("error while creating zip archive: {}", err))?;
+        let mut response = reqwest::get(link)
+            .await
+            .map_err(|err| format!("error while getting test cases response: {}", err))?;
+        // the archives of some problems are large, so a cancel is checked
+        // between chunks instead of after the whole download
+        let mut bytes = vec![];
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|err| format!("error while getting test cases bytes: {}", err))?
+        {
+            if self.cancel.is_cancelled() {
+                return Ok(false);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let mut archive = ZipArchive::new(Cursor::new(bytes))
+            .map_err(|err| format!("error while creating zip archive: {}", err))?;
         for i in 0..archive.len() {
             let mut file = archive.by_index(i).unwrap();
             let output_path = match file.enclosed_name() {
@@ -233,173 +575,611 @@ impl Judge {
                     .map_err(|err| format!("error while copying file data: {}", err))?;
             }
         }
-        Ok(())
+        Ok(true)
+    }
+
+    /// The downloaded tests called `file_names`.
+    fn downloaded_tests(&self, file_names: Vec<String>) -> Result<Vec<TestFiles>, String> {
+        let output_dir = self.output_dir()?;
+        let tests_dir = format!(
+            "{}/test_cases/{}",
+            self.directory,
+            self.problem.test_cases_name()
+        );
+
+        let mut file_names = file_names;
+        file_names.sort();
+        Ok(file_names
+            .into_iter()
+            .map(|file_name| TestFiles {
+                input: PathBuf::from(format!("{}/in/{}", tests_dir, file_name)),
+                output: output_dir.join(&file_name),
+                error: output_dir.join(format!("{}.stderr", file_name)),
+                answer: Some(PathBuf::from(format!("{}/out/{}", tests_dir, file_name))),
+                name: file_name,
+            })
+            .collect())
+    }
+
+    fn custom_tests(&self) -> Result<Vec<TestFiles>, String> {
+        let output_dir = self.output_dir()?.join("custom");
+        fs::create_dir_all(&output_dir)
+            .map_err(|err| format!("error while creating output directory: {}", err))?;
+        let tests = CustomTests::new(&self.directory, &self.problem.test_cases_name());
+        Ok(tests
+            .names()?
+            .into_iter()
+            .map(|name| TestFiles {
+                input: tests.input_path(&name),
+                output: output_dir.join(&name),
+                error: output_dir.join(format!("{}.stderr", name)),
+                answer: Some(tests.answer_path(&name)).filter(|answer| answer.exists()),
+                name: format!("custom/{}", name),
+            })
+            .collect())
     }
 
-    pub fn judge_by_filenames(&mut self, file_names: Vec<String>) -> Result<Vec<Verdict>, String> {
-        if self.binary_path.is_none() {
-            self.compile()?;
+    pub fn judge_tests(&mut self, tests: Vec<TestFiles>) -> Result<Vec<Verdict>, String> {
+        let mut cases = vec![];
+        for files in tests {
+            let mut input = fs::read_to_string(&files.input)
+                .map_err(|err| format!("error while reading input file: {}", err))?;
+            let mut output = match &files.answer {
+                Some(answer) => fs::read_to_string(answer)
+                    .map_err(|err| format!("error while reading output file: {}", err))?,
+                None => String::new(),
+            };
+
+            input = input.trim().replace("\r\n", "\n");
+            output = output.trim().replace("\r\n", "\n");
+
+            cases.push((Verdict::new(input, output), files));
         }
-        let binary_path = self.binary_path.as_ref().unwrap().clone();
 
-        let directory = self.directory.clone();
-        let contest_type = self.problem.contest_type.clone();
-        let contest_id = self.problem.contest_id.clone();
-        let problem_id = self.problem.problem_id.clone();
-        let timeout = Duration::from_secs(self.problem.time_limit + 2);
+        if self.plan.is_none() {
+            (self.listener)(JudgeEvent::CompileStart {});
+            let result = self.compile()?;
+            (self.listener)(JudgeEvent::CompileEnd {
+                result: result.clone(),
+            });
+            if self.cancel.is_cancelled() {
+                return Ok(cases
+                    .into_iter()
+                    .map(|(verdict, _)| verdict.skipped())
+                    .collect());
+            }
+            if !result.success {
+                return Ok(vec![Verdict::compile_error(result)]);
+            }
+        }
+        let plan = self.plan.as_ref().unwrap().clone();
+        let limits = self.limits();
+        let checker = self.checker()?;
 
-        let mut verdicts: Vec<Verdict> = vec![];
+        self.exec_all(cases, &plan, &checker, &limits)
+    }
+
+    fn limits(&self) -> RunLimits {
+        let time_limit =
+            Duration::from_secs_f64(self.problem.time_limit * self.config.time_limit_multiplier);
+        RunLimits {
+            time_limit,
+            // solutions are judged on cpu time, the wall clock only has to
+            // catch the ones that sleep or block on input
+            timeout: time_limit * 2 + Duration::from_secs(1),
+            memory_limit: Some(self.problem.memory_limit * 1024),
+            output_limit: Some(self.config.output_limit * 1024 * 1024),
+        }
+    }
+
+    fn output_dir(&self) -> Result<PathBuf, String> {
         let output_dir = PathBuf::from(format!(
-            "{}/output/{}{}_{}",
-            directory, contest_type, contest_id, problem_id
+            "{}/output/{}",
+            self.directory,
+            self.problem.test_cases_name()
         ));
 
         if !output_dir.exists() {
             fs::create_dir_all(output_dir.clone())
                 .map_err(|err| format!("error while creating output directory: {}", err))?;
         }
+        Ok(output_dir)
+    }
 
-        for file_name in file_names {
-            let input_file = PathBuf::from(format!(
-                "{}/test_cases/{}{}_{}/in/{}",
-                directory, contest_type, contest_id, problem_id, file_name
-            ));
+    fn checker(&self) -> Result<ProblemChecker, String> {
+        Ok(
+            match ExternalChecker::find(&self.directory, &self.problem.file_name())? {
+                Some(external) => ProblemChecker::External(external),
+                None => ProblemChecker::BuiltIn(self.config.checker(&self.problem.file_name())),
+            },
+        )
+    }
 
-            let mut output_file = output_dir.clone();
-            output_file.push(file_name.clone());
+    /// Runs the solution on `input` under the problem's limits, without an
+    /// answer to compare with. The files live in the output folder, away from
+    /// the tests.
+    pub fn run_with_input(&mut self, input: &str) -> Result<RunOutput, String> {
+        let mut output = RunOutput {
+            status: JudgeStatus::OK,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: None,
+            time: None,
+            cpu_time: None,
+            memory: None,
+            compile_result: None,
+        };
 
-            let mut input = fs::read_to_string(&input_file)
-                .map_err(|err| format!("error while reading input file: {}", err))?;
-            let mut output = fs::read_to_string(format!(
-                "{}/test_cases/{}{}_{}/out/{}",
-                directory, contest_type, contest_id, problem_id, file_name
-            ))
+        (self.listener)(JudgeEvent::CompileStart {});
+        let compiled = self.compile()?;
+        (self.listener)(JudgeEvent::CompileEnd {
+            result: compiled.clone(),
+        });
+        if self.cancel.is_cancelled() {
+            output.status = JudgeStatus::SKIP;
+            return Ok(output);
+        }
+        if !compiled.success {
+            output.status = JudgeStatus::CE;
+            output.compile_result = Some(compiled);
+            return Ok(output);
+        }
+        let plan = self.plan.as_ref().unwrap().clone();
+
+        let run_dir = self.output_dir()?.join("run");
+        fs::create_dir_all(&run_dir)
+            .map_err(|err| format!("error while creating run directory: {}", err))?;
+        let (input_file, output_file, error_file) = (
+            run_dir.join("input"),
+            run_dir.join("output"),
+            run_dir.join("error"),
+        );
+        fs::write(&input_file, input)
+            .map_err(|err| format!("error while writing input file: {}", err))?;
+
+        let limits = self.limits();
+        let result =
+            self.runner
+                .run(&plan, &input_file, &output_file, Some(&error_file), &limits)?;
+        output.time = Some(result.time.as_secs_f32());
+        output.cpu_time = result.cpu_time.map(|t| t.as_secs_f32());
+        output.memory = result.memory;
+        output.status = match result.termination {
+            Termination::Exited(status) => {
+                output.exit_code = status.code();
+                if status.success() {
+                    JudgeStatus::OK
+                } else {
+                    JudgeStatus::RE
+                }
+            }
+            Termination::TimedOut => JudgeStatus::TLE,
+            Termination::MemoryLimitExceeded => JudgeStatus::MLE,
+            Termination::OutputLimitExceeded => JudgeStatus::OLE,
+            Termination::Cancelled => JudgeStatus::SKIP,
+        };
+
+        // the solution may print anything, not only utf-8
+        output.stdout = fs::read(&output_file)
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
             .map_err(|err| format!("error while reading output file: {}", err))?;
+        output.stderr = read_stderr(&error_file)?.unwrap_or_default();
+        Ok(output)
+    }
 
-            input = input.trim().replace("\r\n", "\n");
-            output = output.trim().replace("\r\n", "\n");
+    /// Runs the solution against `<name>_brute` on inputs printed by
+    /// `<name>_gen`, which gets the seed as its only argument. Stops at the
+    /// first input the solution does not get accepted on, saving it as a
+    /// custom test, or after `options.iterations` inputs or once
+    /// `options.time_budget` is spent.
+    pub fn stress_test(&mut self, options: &StressOptions) -> Result<StressResult, String> {
+        let mut result = StressResult {
+            iterations: 0,
+            seed: None,
+            verdict: None,
+            test_name: None,
+        };
+
+        (self.listener)(JudgeEvent::CompileStart {});
+        let compiled = self.compile()?;
+        (self.listener)(JudgeEvent::CompileEnd {
+            result: compiled.clone(),
+        });
+        if self.cancel.is_cancelled() {
+            return Ok(result);
+        }
+        if !compiled.success {
+            result.verdict = Some(Verdict::compile_error(compiled));
+            return Ok(result);
+        }
+        let plan = self.plan.as_ref().unwrap().clone();
+        let name = self.problem.file_name();
+        let (Some(generator), Some(brute)) = (
+            self.compile_helper(&format!("{}_gen", name))?,
+            self.compile_helper(&format!("{}_brute", name))?,
+        ) else {
+            return Ok(result);
+        };
+
+        let stress_dir = self.output_dir()?.join("stress");
+        fs::create_dir_all(&stress_dir)
+            .map_err(|err| format!("error while creating stress directory: {}", err))?;
+        let answer_file = stress_dir.join("answer");
+        let files = TestFiles {
+            name: String::new(),
+            input: stress_dir.join("input"),
+            output: stress_dir.join("output"),
+            error: stress_dir.join("error"),
+            answer: Some(answer_file.clone()),
+        };
+        let no_input = stress_dir.join("empty");
+        fs::write(&no_input, "")
+            .map_err(|err| format!("error while creating empty input: {}", err))?;
 
-            let binary_path = binary_path.clone();
+        let limits = self.limits();
+        let helper_limits = RunLimits {
+            time_limit: HELPER_TIME_LIMIT,
+            timeout: HELPER_TIME_LIMIT * 2,
+            memory_limit: None,
+            output_limit: limits.output_limit,
+        };
+        let checker = self.checker()?;
+        let start = Instant::now();
+        for seed in 1..=options.iterations {
+            if start.elapsed().as_secs_f64() > options.time_budget {
+                break;
+            }
+            let mut seeded = generator.clone();
+            seeded.args.push(seed.to_string());
+            if !self.run_helper(
+                "generator",
+                &seeded,
+                &no_input,
+                &files.input,
+                &helper_limits,
+                seed,
+            )? || !self.run_helper(
+                "brute force solution",
+                &brute,
+                &files.input,
+                &answer_file,
+                &helper_limits,
+                seed,
+            )? {
+                break;
+            }
+
+            let input = fs::read_to_string(&files.input)
+                .map_err(|err| format!("error while reading input file: {}", err))?;
+            let answer = fs::read_to_string(&answer_file)
+                .map_err(|err| format!("error while reading output file: {}", err))?;
+            let mut verdict = Verdict::new(
+                input.trim().replace("\r\n", "\n"),
+                answer.trim().replace("\r\n", "\n"),
+            );
+            verdict.exec(self.runner.as_ref(), &plan, &files, &checker, &limits)?;
+            if verdict.status == Some(JudgeStatus::SKIP) {
+                break;
+            }
+            result.iterations = seed;
+            (self.listener)(JudgeEvent::Verdict {
+                index: seed as usize - 1,
+                total: options.iterations as usize,
+                name: format!("seed {}", seed),
+                verdict: Box::new(verdict.clone()),
+            });
 
-            let mut verdict = Verdict::new(input, output);
-            verdict.exec(binary_path, input_file, output_file, timeout.clone())?;
-            verdicts.push(verdict);
+            if verdict.status != Some(JudgeStatus::AC) {
+                let test = CustomTest {
+                    name: format!("stress_{}", seed),
+                    input,
+                    output: Some(answer),
+                };
+                CustomTests::new(&self.directory, &self.problem.test_cases_name()).save(&test)?;
+                result.seed = Some(seed);
+                result.verdict = Some(verdict);
+                result.test_name = Some(test.name);
+                break;
+            }
         }
+        Ok(result)
+    }
 
-        Ok(verdicts)
+    /// Builds a program helping the stress test, bundled into `bin/` as it is
+    /// not submitted. `None` when the stress test was cancelled.
+    fn compile_helper(&self, name: &str) -> Result<Option<RunPlan>, String> {
+        let bundle_path = PathBuf::from(format!(
+            "{}/bin/{}.{}",
+            self.directory, name, self.language.extension
+        ));
+        match self.compile_program(name, &bundle_path)? {
+            (_, Some(plan)) => Ok(Some(plan)),
+            (_, None) if self.cancel.is_cancelled() => Ok(None),
+            (result, None) => Err(format!("error while compiling {}: {}", name, result.stderr)),
+        }
+    }
+
+    /// Runs the generator or the brute force solution, returning `false` when
+    /// the stress test was cancelled.
+    fn run_helper(
+        &self,
+        helper: &str,
+        plan: &RunPlan,
+        input: &Path,
+        output: &Path,
+        limits: &RunLimits,
+        seed: u64,
+    ) -> Result<bool, String> {
+        match self
+            .runner
+            .run(plan, input, output, None, limits)?
+            .termination
+        {
+            Termination::Exited(status) if status.success() => Ok(true),
+            Termination::Cancelled => Ok(false),
+            Termination::Exited(status) => Err(format!(
+                "error while stress testing: the {} failed with {} on seed {}",
+                helper, status, seed
+            )),
+            _ => Err(format!(
+                "error while stress testing: the {} went over its limits on seed {}",
+                helper, seed
+            )),
+        }
+    }
+
+    /// Runs the cases on up to `config.workers` threads, never more than there
+    /// are cores. Each solution is judged on its own cpu time, so running them
+    /// side by side does not eat into each other's time limit.
+    fn exec_all(
+        &self,
+        cases: Vec<(Verdict, TestFiles)>,
+        plan: &RunPlan,
+        checker: &ProblemChecker,
+        limits: &RunLimits,
+    ) -> Result<Vec<Verdict>, String> {
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        let workers = self.config.workers.clamp(1, cores).min(cases.len().max(1));
+
+        let total = cases.len();
+        let next = AtomicUsize::new(0);
+        let results: Vec<Mutex<Option<Result<Verdict, String>>>> =
+            cases.iter().map(|_| Mutex::new(None)).collect();
+        let cases: Vec<Mutex<Option<(Verdict, TestFiles)>>> = cases
+            .into_iter()
+            .map(|case| Mutex::new(Some(case)))
+            .collect();
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(case) = cases.get(index) else {
+                        break;
+                    };
+                    let (mut verdict, files) = case.lock().unwrap().take().unwrap();
+                    let result = if self.cancel.is_cancelled() {
+                        Ok(verdict.skipped())
+                    } else {
+                        verdict
+                            .exec(self.runner.as_ref(), plan, &files, checker, limits)
+                            .map(|_| verdict)
+                    };
+                    if let Ok(verdict) = &result {
+                        (self.listener)(JudgeEvent::Verdict {
+                            index,
+                            total,
+                            name: files.name,
+                            verdict: Box::new(verdict.clone()),
+                        });
+                    }
+                    *results[index].lock().unwrap() = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_iter()
+            .map(|result| result.into_inner().unwrap().unwrap())
+            .collect()
     }
 }
 
 pub async fn submit(
     problem: FullProblem,
     directory: String,
-    language: Language,
+    language: LanguageSpec,
+    config: JudgeConfig,
+    listener: JudgeListener,
+    cancel: CancelToken,
 ) -> Result<Vec<Verdict>, String> {
-    let mut judge = Judge::new(problem.clone(), directory.clone(), language);
-    judge.download_test_cases().await?;
-
-    let in_paths = fs::read_dir(format!(
-        "{}/test_cases/{}{}_{}/in",
-        directory, problem.contest_type, problem.contest_id, problem.problem_id
-    ))
-    .map_err(|err| format!("error while reading in directory: {}", err))?;
-    let out_paths = fs::read_dir(format!(
-        "{}/test_cases/{}{}_{}/out",
-        directory, problem.contest_type, problem.contest_id, problem.problem_id
-    ))
-    .map_err(|err| format!("error while reading out directory: {}", err))?;
-
-    let mut file_names_map = HashMap::new();
-    for path in in_paths {
-        let path = path.unwrap().path();
-        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-        file_names_map.insert(file_name, 1);
+    let mut judge = Judge::new(
+        problem.clone(),
+        directory.clone(),
+        language,
+        config,
+        listener,
+        cancel,
+        BuildProfile::Release,
+    )?;
+    if !judge.download_test_cases().await? {
+        return Ok(vec![]);
     }
 
-    for path in out_paths {
-        let path = path.unwrap().path();
-        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-        if file_names_map.contains_key(&file_name) {
-            file_names_map.insert(file_name, 2);
+    // compiling and judging block, so they are kept off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let in_paths = fs::read_dir(format!(
+            "{}/test_cases/{}/in",
+            directory,
+            problem.test_cases_name()
+        ))
+        .map_err(|err| format!("error while reading in directory: {}", err))?;
+        let out_paths = fs::read_dir(format!(
+            "{}/test_cases/{}/out",
+            directory,
+            problem.test_cases_name()
+        ))
+        .map_err(|err| format!("error while reading out directory: {}", err))?;
+
+        let mut file_names_map = HashMap::new();
+        for path in in_paths {
+            let path = path.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+            file_names_map.insert(file_name, 1);
         }
-    }
 
-    let mut file_names = vec![];
-    for (file_name, status) in file_names_map {
-        if status == 2 {
-            file_names.push(file_name);
+        for path in out_paths {
+            let path = path.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+            if file_names_map.contains_key(&file_name) {
+                file_names_map.insert(file_name, 2);
+            }
         }
-    }
 
-    let verdicts = judge.judge_by_filenames(file_names)?;
-
-    if verdicts.iter().all(|x| {
-        x.status
-            .as_ref()
-            .unwrap_or(&JudgeStatus::CE)
-            .eq(&JudgeStatus::AC)
-    }) {
-        insert_solved_problem(
-            BareProblem::new(
-                problem.contest_type,
-                problem.contest_id,
-                problem.problem_id,
-                "".into(),
-            ),
-            directory,
-        )?;
-    }
+        let mut file_names = vec![];
+        for (file_name, status) in file_names_map {
+            if status == 2 {
+                file_names.push(file_name);
+            }
+        }
+
+        let tests = judge.downloaded_tests(file_names)?;
+        let verdicts = judge.judge_tests(tests)?;
 
-    Ok(verdicts)
+        if verdicts.iter().all(|x| {
+            x.status
+                .as_ref()
+                .unwrap_or(&JudgeStatus::CE)
+                .eq(&JudgeStatus::AC)
+        }) {
+            insert_solved_problem(
+                BareProblem::new(
+                    problem.contest_type,
+                    problem.contest_id,
+                    problem.problem_id,
+                    "".into(),
+                ),
+                directory,
+            )?;
+        }
+
+        Ok(verdicts)
+    })
+    .await
+    .map_err(|err| format!("error while judging: {}", err))?
 }
 
 pub async fn run(
     problem: FullProblem,
     directory: String,
-    language: Language,
+    language: LanguageSpec,
+    config: JudgeConfig,
+    listener: JudgeListener,
+    cancel: CancelToken,
+    profile: BuildProfile,
 ) -> Result<Vec<Verdict>, String> {
-    let mut judge = Judge::new(problem.clone(), directory.clone(), language);
-    judge.download_test_cases().await?;
-
-    let in_paths = fs::read_dir(format!(
-        "{}/test_cases/{}{}_{}/in",
-        directory, problem.contest_type, problem.contest_id, problem.problem_id
-    ))
-    .map_err(|err| format!("error while reading in directory: {}", err))?;
-    let out_paths = fs::read_dir(format!(
-        "{}/test_cases/{}{}_{}/out",
-        directory, problem.contest_type, problem.contest_id, problem.problem_id
-    ))
-    .map_err(|err| format!("error while reading out directory: {}", err))?;
-
-    let mut file_names_map = HashMap::new();
-    for path in in_paths {
-        let path = path.unwrap().path();
-        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-        if !file_name.contains("sample") && !file_name.contains("example") {
-            continue;
-        }
-        file_names_map.insert(file_name, 1);
+    let mut judge = Judge::new(
+        problem.clone(),
+        directory.clone(),
+        language,
+        config,
+        listener,
+        cancel,
+        profile,
+    )?;
+    if !judge.download_test_cases().await? {
+        return Ok(vec![]);
     }
-    for path in out_paths {
-        let path = path.unwrap().path();
-        let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
-        if !file_name.contains("sample") {
-            continue;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let in_paths = fs::read_dir(format!(
+            "{}/test_cases/{}/in",
+            directory,
+            problem.test_cases_name()
+        ))
+        .map_err(|err| format!("error while reading in directory: {}", err))?;
+        let out_paths = fs::read_dir(format!(
+            "{}/test_cases/{}/out",
+            directory,
+            problem.test_cases_name()
+        ))
+        .map_err(|err| format!("error while reading out directory: {}", err))?;
+
+        let mut file_names_map = HashMap::new();
+        for path in in_paths {
+            let path = path.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+            if !file_name.contains("sample") && !file_name.contains("example") {
+                continue;
+            }
+            file_names_map.insert(file_name, 1);
         }
-        if file_names_map.contains_key(&file_name) {
-            file_names_map.insert(file_name, 2);
+        for path in out_paths {
+            let path = path.unwrap().path();
+            let file_name = path.file_name().unwrap().to_str().unwrap().to_string();
+            if !file_name.contains("sample") {
+                continue;
+            }
+            if file_names_map.contains_key(&file_name) {
+                file_names_map.insert(file_name, 2);
+            }
         }
-    }
 
-    let mut file_names = vec![];
-    for (file_name, status) in file_names_map {
-        if status == 2 {
-            file_names.push(file_name);
+        let mut file_names = vec![];
+        for (file_name, status) in file_names_map {
+            if status == 2 {
+                file_names.push(file_name);
+            }
         }
-    }
 
-    let verdicts = judge.judge_by_filenames(file_names)?;
+        let mut tests = judge.downloaded_tests(file_names)?;
+        tests.extend(judge.custom_tests()?);
+        let verdicts = judge.judge_tests(tests)?;
+
+        Ok(verdicts)
+    })
+    .await
+    .map_err(|err| format!("error while judging: {}", err))?
+}
+
+pub fn stress_test(
+    problem: FullProblem,
+    directory: String,
+    language: LanguageSpec,
+    config: JudgeConfig,
+    listener: JudgeListener,
+    cancel: CancelToken,
+    options: StressOptions,
+) -> Result<StressResult, String> {
+    let mut judge = Judge::new(
+        problem,
+        directory,
+        language,
+        config,
+        listener,
+        cancel,
+        BuildProfile::Release,
+    )?;
+    judge.stress_test(&options)
+}
 
-    Ok(verdicts)
+pub fn run_with_input(
+    problem: FullProblem,
+    directory: String,
+    language: LanguageSpec,
+    config: JudgeConfig,
+    listener: JudgeListener,
+    cancel: CancelToken,
+    input: String,
+) -> Result<RunOutput, String> {
+    let mut judge = Judge::new(
+        problem,
+        directory,
+        language,
+        config,
+        listener,
+        cancel,
+        BuildProfile::Release,
+    )?;
+    judge.run_with_input(&input)
 }
@@ -0,0 +1,476 @@
+use std::collections::HashMap;
+use std::env::consts::EXE_SUFFIX;
+use std::fs::File;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runner::RunPlan;
+
+/// How a language is built and run. Each argument may use the `{source}` and
+/// `{binary}` placeholders, filled in with the paths of the problem's files.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Toolchain {
+    /// command building `{binary}`, empty for languages run from source
+    #[serde(default)]
+    pub compile: Vec<String>,
+    /// command building `{binary}` with runtime checks, like sanitizers, for
+    /// debug runs. `compile` is used when empty.
+    #[serde(default)]
+    pub debug_compile: Vec<String>,
+    pub run: Vec<String>,
+    /// set for both the compiler and the solution
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// command printing the version of the compiler or interpreter,
+    /// `<program> --version` when empty
+    #[serde(default)]
+    pub version: Vec<String>,
+}
+
+/// Which flags a solution is built with.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BuildProfile {
+    /// the flags AtCoder builds with
+    Release,
+    /// the flags of `Toolchain::debug_compile`, into a binary of its own
+    Debug,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LanguageSpec {
+    pub id: String,
+    pub name: String,
+    pub extension: String,
+    pub source_directory: String,
+    pub compiled: bool,
+    /// extension of what the compiler writes, for compilers that do not build
+    /// native executables
+    #[serde(default)]
+    pub binary_extension: Option<String>,
+    /// id of the language on AtCoder's submit page
+    #[serde(default)]
+    pub atcoder_id: Option<u32>,
+    #[serde(flatten)]
+    pub toolchain: Toolchain,
+}
+
+impl LanguageSpec {
+    /// The compiler of a compiled language, the interpreter of the others.
+    pub fn program(&self) -> Option<&str> {
+        let template = if self.compiled {
+            &self.toolchain.compile
+        } else {
+            &self.toolchain.run
+        };
+        template.first().map(String::as_str)
+    }
+
+    pub fn compile_command(&self, profile: BuildProfile) -> &[String] {
+        match profile {
+            BuildProfile::Debug if !self.toolchain.debug_compile.is_empty() => {
+                &self.toolchain.debug_compile
+            }
+            _ => &self.toolchain.compile,
+        }
+    }
+
+    pub fn version_command(&self) -> Vec<String> {
+        if !self.toolchain.version.is_empty() {
+            return self.toolchain.version.clone();
+        }
+        self.program()
+            .map(|program| vec![program.to_string(), "--version".into()])
+            .unwrap_or_default()
+    }
+
+    /// Name of the file the compiler writes to `bin/` for a problem.
+    pub fn binary_file_name(&self, problem_name: &str) -> String {
+        match &self.binary_extension {
+            Some(extension) => format!("{}.{}", problem_name, extension),
+            None => format!("{}{}", problem_name, EXE_SUFFIX),
+        }
+    }
+
+    /// How a solution is started, running `binary` for compiled languages and
+    /// `source` for the others.
+    pub fn run_plan(
+        &self,
+        source: &Path,
+        binary: &Path,
+        working_dir: &Path,
+    ) -> Result<RunPlan, String> {
+        let mut command = self.expand(&self.toolchain.run, source, binary)?;
+        let program = command.remove(0);
+        Ok(RunPlan {
+            program,
+            args: command,
+            working_dir: working_dir.to_path_buf(),
+            env: self.toolchain.env.clone(),
+        })
+    }
+
+    /// Fills the placeholders of a command template.
+    pub fn expand(
+        &self,
+        template: &[String],
+        source: &Path,
+        binary: &Path,
+    ) -> Result<Vec<String>, String> {
+        if template.is_empty() {
+            return Err(format!("no command is configured for {}", self.name));
+        }
+        Ok(template
+            .iter()
+            .map(|arg| {
+                arg.replace("{source}", &source.to_string_lossy())
+                    .replace("{binary}", &binary.to_string_lossy())
+            })
+            .collect())
+    }
+}
+
+/// The languages that can be judged: the built-in ones, changed or extended
+/// by the entries in `xcoder_languages.json` in the data dir.
+pub struct LanguageRegistry {
+    languages: Vec<LanguageSpec>,
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> LanguageRegistry {
+        LanguageRegistry {
+            languages: built_in_languages(),
+        }
+    }
+}
+
+impl LanguageRegistry {
+    pub fn load() -> Result<LanguageRegistry, String> {
+        let mut registry = LanguageRegistry::default();
+        let path = registry_path();
+        if !Path::new(&path).exists() {
+            return Ok(registry);
+        }
+
+        let entries: Vec<LanguageSpec> = serde_json::from_reader(
+            File::open(&path)
+                .map_err(|err| format!("error while opening xcoder_languages.json: {}", err))?,
+        )
+        .map_err(|err| format!("error while parsing xcoder_languages.json: {}", err))?;
+        for entry in entries {
+            match registry.position(&entry.id) {
+                Some(index) => registry.languages[index] = entry,
+                None => registry.languages.push(entry),
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Writes the languages that differ from the built-in ones.
+    pub fn save(&self) -> Result<(), String> {
+        let built_in = built_in_languages();
+        let entries: Vec<&LanguageSpec> = self
+            .languages
+            .iter()
+            .filter(|language| !built_in.contains(language))
+            .collect();
+        serde_json::to_writer_pretty(
+            File::create(registry_path())
+                .map_err(|err| format!("error while creating xcoder_languages.json: {}", err))?,
+            &entries,
+        )
+        .map_err(|err| format!("error while writing xcoder_languages.json: {}", err))
+    }
+
+    pub fn languages(&self) -> &[LanguageSpec] {
+        &self.languages
+    }
+
+    /// Looks a language up by its id or its name, ignoring case.
+    pub fn get(&self, language: &str) -> Result<&LanguageSpec, String> {
+        self.position(language)
+            .map(|index| &self.languages[index])
+            .ok_or(format!("unknown language {}", language))
+    }
+
+    pub fn set_toolchain(&mut self, language: &str, toolchain: Toolchain) -> Result<(), String> {
+        let index = self
+            .position(language)
+            .ok_or(format!("unknown language {}", language))?;
+        self.languages[index].toolchain = toolchain;
+        self.save()
+    }
+
+    pub fn reset_toolchain(&mut self, language: &str) -> Result<(), String> {
+        let index = self
+            .position(language)
+            .ok_or(format!("unknown language {}", language))?;
+        let default = built_in_languages()
+            .into_iter()
+            .find(|spec| spec.id == self.languages[index].id)
+            .ok_or(format!("{} has no default toolchain", language))?;
+        self.languages[index].toolchain = default.toolchain;
+        self.save()
+    }
+
+    fn position(&self, language: &str) -> Option<usize> {
+        self.languages.iter().position(|spec| {
+            spec.id.eq_ignore_ascii_case(language) || spec.name.eq_ignore_ascii_case(language)
+        })
+    }
+}
+
+fn registry_path() -> String {
+    let data_dir = tauri::api::path::data_dir().unwrap();
+    format!("{}/xcoder_languages.json", data_dir.display())
+}
+
+fn built_in_languages() -> Vec<LanguageSpec> {
+    let compiled = |id: &str, name: &str, extension: &str, directory: &str, compile: &[&str]| {
+        spec(id, name, extension, directory, true, compile, &["{binary}"])
+    };
+    let interpreted = |id: &str, name: &str, extension: &str, directory: &str, run: &[&str]| {
+        spec(id, name, extension, directory, false, &[], run)
+    };
+
+    // flags and ids follow AtCoder's language table, languages whose id is
+    // not known are left for the user to pick on the submit page. ubsan only
+    // fails the run with -fno-sanitize-recover, it just prints otherwise.
+    vec![
+        compiled(
+            "c",
+            "C",
+            "c",
+            ".",
+            &[
+                "gcc",
+                "-o",
+                "{binary}",
+                "{source}",
+                "-std=gnu2x",
+                "-O2",
+                "-DONLINE_JUDGE",
+                "-DATCODER",
+                "-lm",
+            ],
+        )
+        .debug(&[
+            "gcc",
+            "-o",
+            "{binary}",
+            "{source}",
+            "-std=gnu2x",
+            "-g",
+            "-fsanitize=address,undefined",
+            "-fno-sanitize-recover=all",
+            "-fno-omit-frame-pointer",
+            "-DATCODER",
+            "-lm",
+        ])
+        .atcoder(5017),
+        compiled(
+            "cpp",
+            "C++",
+            "cpp",
+            ".",
+            &[
+                "g++",
+                "-o",
+                "{binary}",
+                "{source}",
+                "-std=gnu++20",
+                "-O2",
+                "-DONLINE_JUDGE",
+                "-DATCODER",
+            ],
+        )
+        .debug(&[
+            "g++",
+            "-o",
+            "{binary}",
+            "{source}",
+            "-std=gnu++20",
+            "-g",
+            "-fsanitize=address,undefined",
+            "-fno-sanitize-recover=all",
+            "-fno-omit-frame-pointer",
+            "-D_GLIBCXX_DEBUG",
+            "-DATCODER",
+        ])
+        .atcoder(5001),
+        compiled(
+            "go",
+            "Go",
+            "go",
+            ".",
+            &["go", "build", "-o", "{binary}", "{source}"],
+        )
+        .version(&["go", "version"])
+        .atcoder(5002),
+        compiled(
+            "rust",
+            "Rust",
+            "rs",
+            "src/bin",
+            &[
+                "rustc",
+                "-o",
+                "{binary}",
+                "{source}",
+                "--edition",
+                "2021",
+                "-O",
+            ],
+        )
+        .debug(&[
+            "rustc",
+            "-o",
+            "{binary}",
+            "{source}",
+            "--edition",
+            "2021",
+            "-g",
+            "-C",
+            "debug-assertions",
+            "-C",
+            "overflow-checks",
+        ])
+        .atcoder(5054),
+        compiled(
+            "kotlin",
+            "Kotlin",
+            "kt",
+            "src",
+            &["kotlinc", "{source}", "-include-runtime", "-d", "{binary}"],
+        )
+        .runs(&["java", "-jar", "{binary}"])
+        .binary_extension("jar")
+        .version(&["kotlinc", "-version"])
+        .atcoder(5004),
+        compiled(
+            "zig",
+            "Zig",
+            "zig",
+            ".",
+            &[
+                "zig",
+                "build-exe",
+                "-O",
+                "ReleaseFast",
+                "-femit-bin={binary}",
+                "{source}",
+            ],
+        )
+        .version(&["zig", "version"])
+        .atcoder(5008),
+        interpreted("node", "Node", "js", ".", &["node", "{source}"]).atcoder(5009),
+        compiled(
+            "swift",
+            "Swift",
+            "swift",
+            ".",
+            &["swiftc", "-o", "{binary}", "{source}", "-Ounchecked"],
+        )
+        .atcoder(5014),
+        compiled(
+            "dart",
+            "Dart",
+            "dart",
+            ".",
+            &["dart", "compile", "exe", "{source}", "-o", "{binary}"],
+        )
+        .atcoder(5015),
+        compiled(
+            "haskell",
+            "Haskell",
+            "hs",
+            "src",
+            &["ghc", "-o", "{binary}", "{source}", "-O2"],
+        )
+        .atcoder(5025),
+        interpreted("elixir", "Elixir", "exs", "lib", &["elixir", "{source}"]),
+        compiled(
+            "fortran",
+            "Fortran",
+            "f90",
+            ".",
+            &["gfortran", "-o", "{binary}", "{source}", "-O2"],
+        ),
+        compiled(
+            "ocaml",
+            "OCaml",
+            "ml",
+            ".",
+            &["ocamlopt", "-o", "{binary}", "{source}"],
+        )
+        .version(&["ocamlopt", "-version"]),
+        // python3 is not installed as python on most unix systems
+        interpreted(
+            "python",
+            "Python",
+            "py",
+            ".",
+            &[if cfg!(windows) { "python" } else { "python3" }, "{source}"],
+        )
+        .atcoder(5055),
+        interpreted("julia", "Julia", "jl", ".", &["julia", "{source}"]).atcoder(5022),
+        interpreted("fsharp", "F#", "fsx", ".", &["dotnet", "fsi", "{source}"]).atcoder(5021),
+        // needs the dotnet-script tool, as plain dotnet only builds projects
+        interpreted("csharp", "C#", "cs", ".", &["dotnet", "script", "{source}"]).atcoder(5003),
+    ]
+}
+
+fn spec(
+    id: &str,
+    name: &str,
+    extension: &str,
+    source_directory: &str,
+    compiled: bool,
+    compile: &[&str],
+    run: &[&str],
+) -> LanguageSpec {
+    let strings = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+    LanguageSpec {
+        id: id.into(),
+        name: name.into(),
+        extension: extension.into(),
+        source_directory: source_directory.into(),
+        compiled,
+        binary_extension: None,
+        atcoder_id: None,
+        toolchain: Toolchain {
+            compile: strings(compile),
+            debug_compile: vec![],
+            run: strings(run),
+            env: HashMap::new(),
+            version: vec![],
+        },
+    }
+}
+
+impl LanguageSpec {
+    fn runs(mut self, command: &[&str]) -> LanguageSpec {
+        self.toolchain.run = command.iter().map(|arg| arg.to_string()).collect();
+        self
+    }
+
+    fn binary_extension(mut self, extension: &str) -> LanguageSpec {
+        self.binary_extension = Some(extension.into());
+        self
+    }
+
+    fn version(mut self, command: &[&str]) -> LanguageSpec {
+        self.toolchain.version = command.iter().map(|arg| arg.to_string()).collect();
+        self
+    }
+
+    fn debug(mut self, command: &[&str]) -> LanguageSpec {
+        self.toolchain.debug_compile = command.iter().map(|arg| arg.to_string()).collect();
+        self
+    }
+
+    fn atcoder(mut self, id: u32) -> LanguageSpec {
+        self.atcoder_id = Some(id);
+        self
+    }
+}
@@ -2,22 +2,39 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use commands::{
-    create_file, get_contest_type, get_directory, get_editor, get_language, get_problem,
-    get_problem_type, get_show_solved, new_directory, next, open_file, previous, run, save_state,
-    set_contest_type, set_directory, set_editor, set_language, set_problem_type, set_show_solved,
-    submit, update_problems_list,
+    add_custom_test, cancel_judge, create_file, debug_run, delete_custom_test, detect_toolchains,
+    edit_custom_test, get_checker, get_contest_type, get_directory, get_editor, get_judge_config,
+    get_language, get_languages, get_languages_error, get_problem, get_problem_type,
+    get_show_solved, get_toolchain, list_custom_tests, new_directory, next, open_file,
+    prepare_submission, previous, reset_toolchain, run, run_with_input, save_state, set_checker,
+    set_contest_type, set_directory, set_editor, set_judge_config, set_language, set_problem_type,
+    set_show_solved, set_toolchain, stress_test, submit, update_problems_list,
 };
 
+use crate::judge::JudgeSessions;
 use crate::store::StoreState;
 
+mod bundle;
+mod cache;
+mod checker;
 mod commands;
+mod custom_tests;
+mod detect;
+mod diagnostics;
 mod judge;
+mod language;
 mod problem;
+mod project;
+mod runner;
+#[cfg(target_os = "linux")]
+mod sandbox;
 mod store;
+mod template;
 
 fn main() {
     tauri::Builder::default()
         .manage(StoreState::new())
+        .manage(JudgeSessions::default())
         .invoke_handler(tauri::generate_handler![
             new_directory,
             set_directory,
@@ -32,15 +49,34 @@ fn main() {
             get_language,
             set_show_solved,
             get_show_solved,
+            set_judge_config,
+            get_judge_config,
+            set_checker,
+            get_checker,
+            list_custom_tests,
+            add_custom_test,
+            edit_custom_test,
+            delete_custom_test,
+            get_languages,
+            get_languages_error,
+            detect_toolchains,
+            get_toolchain,
+            set_toolchain,
+            reset_toolchain,
             get_problem,
             next,
             previous,
             run,
+            debug_run,
+            run_with_input,
             submit,
+            cancel_judge,
+            stress_test,
             update_problems_list,
             save_state,
             create_file,
-            open_file
+            open_file,
+            prepare_submission
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
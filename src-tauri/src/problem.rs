@@ -67,7 +67,8 @@ pub struct FullProblem {
     pub problem_id: ProblemId,
     pub title: String,
     pub description: String,
-    pub time_limit: u64,
+    /// in seconds, some problems have fractional limits like 2.5 sec
+    pub time_limit: f64,
     pub memory_limit: u64,
     pub test_cases_link: String,
 }
@@ -96,11 +97,34 @@ impl Problem {
 }
 
 impl FullProblem {
+    /// See `BareProblem::file_name`.
+    pub fn file_name(&self) -> String {
+        self.bare().file_name()
+    }
+
+    pub fn url(&self) -> String {
+        self.bare().url()
+    }
+
+    /// See `BareProblem::test_cases_name`.
+    pub fn test_cases_name(&self) -> String {
+        self.bare().test_cases_name()
+    }
+
+    fn bare(&self) -> BareProblem {
+        BareProblem::new(
+            self.contest_type.clone(),
+            self.contest_id,
+            self.problem_id.clone(),
+            self.test_cases_link.clone(),
+        )
+    }
+
     pub fn new(
         bare_problem: &BareProblem,
         title: String,
         description: String,
-        time_limit: u64,
+        time_limit: f64,
         memory_limit: u64,
     ) -> FullProblem {
         FullProblem {
@@ -117,6 +141,25 @@ impl FullProblem {
 }
 
 impl BareProblem {
+    /// Name the solution, checker and other per problem files are saved under.
+    pub fn file_name(&self) -> String {
+        format!(
+            "{}{}_{}",
+            self.contest_type.to_string().to_lowercase(),
+            self.contest_id,
+            self.problem_id
+        )
+    }
+
+    /// Folder the downloaded and custom tests of the problem are kept in,
+    /// like `ABC123_A`.
+    pub fn test_cases_name(&self) -> String {
+        format!(
+            "{}{}_{}",
+            self.contest_type, self.contest_id, self.problem_id
+        )
+    }
+
     pub fn new(
         contest_type: ContestType,
         contest_id: u16,
@@ -131,16 +174,31 @@ impl BareProblem {
         }
     }
 
-    pub async fn scrape(&self) -> Result<FullProblem, String> {
-        let mut text = reqwest::get(format!(
+    pub fn url(&self) -> String {
+        format!(
             "https://atcoder.jp/contests/{}{:03}/tasks/{}{:03}_{}",
             self.contest_type, self.contest_id, self.contest_type, self.contest_id, self.problem_id
-        ))
-        .await
-        .map_err(|err| format!("error while getting problem response: {}", err))?
-        .text()
-        .await
-        .map_err(|err| format!("error while getting problem text: {}", err))?;
+        )
+    }
+
+    /// Submit page with the problem already selected.
+    pub fn submit_url(&self) -> String {
+        let contest = format!("{}{:03}", self.contest_type, self.contest_id).to_lowercase();
+        format!(
+            "https://atcoder.jp/contests/{}/submit?taskScreenName={}_{}",
+            contest,
+            contest,
+            self.problem_id.to_string().to_lowercase()
+        )
+    }
+
+    pub async fn scrape(&self) -> Result<FullProblem, String> {
+        let mut text = reqwest::get(self.url())
+            .await
+            .map_err(|err| format!("error while getting problem response: {}", err))?
+            .text()
+            .await
+            .map_err(|err| format!("error while getting problem text: {}", err))?;
 
         text = text.replace("\\leq", "≤");
         let document = Html::parse_document(&text);
@@ -177,7 +235,7 @@ impl BareProblem {
             .join("\n");
 
         let limits = limits_text.split("/");
-        let time_limit: u64 = limits
+        let time_limit: f64 = limits
             .clone()
             .nth(0)
             .ok_or(format!("error while getting time limit"))?
@@ -200,7 +258,7 @@ impl BareProblem {
             self,
             title,
             description.inner_html(),
-            time_limit * 2,
+            time_limit,
             memory_limit,
         ))
     }
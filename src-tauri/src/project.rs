@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use zip::ZipArchive;
+
+use crate::language::{BuildProfile, LanguageSpec};
+use crate::runner::{no_window, RunPlan};
+
+/// Build tools that own the workspace. They are used instead of calling the
+/// compiler on a single file, so that the dependencies of the project, like
+/// the crates AtCoder provides, are available to solutions.
+pub enum Project {
+    /// `Cargo.toml` next to `src/bin`, each solution being its own bin target
+    Cargo,
+    /// `build.gradle(.kts)` applying the `application` plugin, whose
+    /// `installDist` task gathers the runtime classpath. Solutions are run
+    /// through their file level class, `abc123_A.kt` as `Abc123_AKt`, so they
+    /// must not declare a package. They are kept in `src`, which gradle only
+    /// compiles once it is added to the main source set, as with
+    /// `kotlin.sourceSets["main"].kotlin.srcDir("src")`.
+    Gradle,
+    /// `stack.yaml` with an executable per solution, named like its file
+    Stack,
+}
+
+impl Project {
+    pub fn detect(language: &LanguageSpec, directory: &Path) -> Option<Project> {
+        let exists = |file: &str| directory.join(file).exists();
+        match language.id.as_str() {
+            "rust" if exists("Cargo.toml") => Some(Project::Cargo),
+            "kotlin" if exists("build.gradle.kts") || exists("build.gradle") => {
+                Some(Project::Gradle)
+            }
+            "haskell" if exists("stack.yaml") => Some(Project::Stack),
+            _ => None,
+        }
+    }
+
+    /// Builds the solution called `name`, leaving the project's own tool to
+    /// decide what is out of date. Only cargo has a debug profile of its own,
+    /// the others build the same way for both.
+    pub fn build_command(&self, directory: &Path, name: &str, profile: BuildProfile) -> Command {
+        let mut command = match self {
+            Project::Cargo => {
+                let mut command = Command::new("cargo");
+                command.args(["build", "--offline", "--bin", name]);
+                if profile == BuildProfile::Release {
+                    command.arg("--release");
+                }
+                command
+            }
+            Project::Gradle => {
+                let mut command = Command::new(gradle(directory));
+                command.args(["--offline", "-q", "installDist"]);
+                command
+            }
+            Project::Stack => {
+                let mut command = Command::new("stack");
+                command.arg("build");
+                command
+            }
+        };
+        no_window(&mut command).current_dir(directory);
+        command
+    }
+
+    pub fn run_plan(
+        &self,
+        directory: &Path,
+        name: &str,
+        env: &HashMap<String, String>,
+        profile: BuildProfile,
+    ) -> Result<RunPlan, String> {
+        let (program, args) = match self {
+            Project::Cargo => {
+                let target = match profile {
+                    BuildProfile::Release => "release",
+                    BuildProfile::Debug => "debug",
+                };
+                let binary = cargo_target_dir(directory)?
+                    .join(target)
+                    .join(format!("{}{}", name, EXE_SUFFIX));
+                (binary.to_string_lossy().to_string(), vec![])
+            }
+            Project::Gradle => {
+                let lib_dir = gradle_lib_dir(directory)?;
+                let main_class = kotlin_main_class(name);
+                // a build that left the solution out still succeeds, it would
+                // only show as a ClassNotFoundException at runtime
+                if !has_class(&lib_dir, &main_class)? {
+                    return Err(format!(
+                        "error while finding {}: gradle did not build it, add `src` to the main \
+                         source set of the project",
+                        main_class
+                    ));
+                }
+                let classpath = lib_dir.join("*");
+                (
+                    "java".to_string(),
+                    vec![
+                        "-cp".to_string(),
+                        classpath.to_string_lossy().to_string(),
+                        main_class,
+                    ],
+                )
+            }
+            Project::Stack => {
+                let binary = stack_install_root(directory)?
+                    .join("bin")
+                    .join(format!("{}{}", name, EXE_SUFFIX));
+                (binary.to_string_lossy().to_string(), vec![])
+            }
+        };
+        Ok(RunPlan {
+            program,
+            args,
+            working_dir: directory.to_path_buf(),
+            env: env.clone(),
+        })
+    }
+}
+
+/// Asks cargo, so that `CARGO_TARGET_DIR` and `.cargo/config.toml` are honoured.
+fn cargo_target_dir(directory: &Path) -> Result<PathBuf, String> {
+    let output = no_window(&mut Command::new("cargo"))
+        .args([
+            "metadata",
+            "--format-version",
+            "1",
+            "--no-deps",
+            "--offline",
+        ])
+        .current_dir(directory)
+        .output()
+        .map_err(|err| format!("error while reading cargo metadata: {}", err))?;
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|err| format!("error while parsing cargo metadata: {}", err))?;
+    metadata["target_directory"]
+        .as_str()
+        .map(PathBuf::from)
+        .ok_or("error while reading cargo metadata: no target directory".into())
+}
+
+fn gradle(directory: &Path) -> PathBuf {
+    let wrapper = if cfg!(windows) {
+        "gradlew.bat"
+    } else {
+        "gradlew"
+    };
+    if directory.join(wrapper).exists() {
+        directory.join(wrapper)
+    } else {
+        PathBuf::from("gradle")
+    }
+}
+
+/// `installDist` writes to `build/install/<project name>/lib`.
+fn gradle_lib_dir(directory: &Path) -> Result<PathBuf, String> {
+    fs::read_dir(directory.join("build/install"))
+        .map_err(|err| format!("error while finding gradle distribution: {}", err))?
+        .flatten()
+        .map(|entry| entry.path().join("lib"))
+        .find(|lib| lib.is_dir())
+        .ok_or("error while finding gradle distribution: no lib folder".into())
+}
+
+/// Whether one of the jars in `lib_dir` has `class` in the default package.
+fn has_class(lib_dir: &Path, class: &str) -> Result<bool, String> {
+    let entry = format!("{}.class", class);
+    let jars = fs::read_dir(lib_dir)
+        .map_err(|err| format!("error while reading gradle distribution: {}", err))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "jar"));
+    for jar in jars {
+        let file = fs::File::open(&jar)
+            .map_err(|err| format!("error while opening {}: {}", jar.display(), err))?;
+        let archive = ZipArchive::new(file)
+            .map_err(|err| format!("error while reading {}: {}", jar.display(), err))?;
+        if archive.file_names().any(|name| name == entry) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn kotlin_main_class(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => format!("{}{}Kt", first.to_uppercase(), chars.as_str()),
+        None => String::new(),
+    }
+}
+
+fn stack_install_root(directory: &Path) -> Result<PathBuf, String> {
+    let output = no_window(&mut Command::new("stack"))
+        .args(["path", "--local-install-root"])
+        .current_dir(directory)
+        .output()
+        .map_err(|err| format!("error while reading stack install root: {}", err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "error while reading stack install root: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(target_os = "linux")]
+use crate::sandbox::Sandbox;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// builds take seconds, they do not need to be polled as often as solutions
+const BUILD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+pub struct RunLimits {
+    /// cpu time the solution is judged against
+    pub time_limit: Duration,
+    /// wall clock time after which the solution is killed
+    pub timeout: Duration,
+    /// peak resident memory in kilobytes
+    pub memory_limit: Option<u64>,
+    /// size of the output file in bytes
+    pub output_limit: Option<u64>,
+}
+
+pub enum Termination {
+    Exited(ExitStatus),
+    TimedOut,
+    MemoryLimitExceeded,
+    OutputLimitExceeded,
+    /// killed because the judge run was cancelled
+    Cancelled,
+}
+
+/// Shared flag that stops a judge run, killing whatever solution is running.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+pub struct RunResult {
+    pub termination: Termination,
+    pub time: Duration,
+    /// user and system cpu time, if the platform reports it
+    pub cpu_time: Option<Duration>,
+    /// peak resident memory in kilobytes, if the platform reports it
+    pub memory: Option<u64>,
+}
+
+/// How a solution that did not exit successfully ended.
+pub struct ExitReason {
+    /// name of the signal that killed the solution, like `SIGSEGV`
+    pub signal: Option<String>,
+    /// what usually makes a solution end this way
+    pub description: String,
+}
+
+#[cfg(unix)]
+pub fn exit_reason(status: &ExitStatus) -> ExitReason {
+    use std::os::unix::process::ExitStatusExt;
+
+    let Some(signal) = status.signal() else {
+        return ExitReason {
+            signal: None,
+            description: format!("exited with code {}", status.code().unwrap_or(-1)),
+        };
+    };
+    let (name, description) = match signal {
+        libc::SIGSEGV => (
+            Some("SIGSEGV"),
+            "segmentation fault, out of bounds access or stack overflow",
+        ),
+        libc::SIGBUS => (Some("SIGBUS"), "bus error, invalid memory access"),
+        libc::SIGFPE => (
+            Some("SIGFPE"),
+            "arithmetic error, like an integer division by zero",
+        ),
+        libc::SIGABRT => (
+            Some("SIGABRT"),
+            "aborted, by a failed assertion or an uncaught exception",
+        ),
+        libc::SIGILL => (Some("SIGILL"), "illegal instruction"),
+        libc::SIGKILL => (Some("SIGKILL"), "killed"),
+        libc::SIGPIPE => (Some("SIGPIPE"), "wrote to a closed pipe"),
+        libc::SIGTRAP => (Some("SIGTRAP"), "trapped, like by a debug trap"),
+        _ => (None, "killed by a signal"),
+    };
+    let name = name.map_or(format!("signal {}", signal), String::from);
+    ExitReason {
+        description: format!("{} ({})", description, name),
+        signal: Some(name),
+    }
+}
+
+/// Windows reports crashes as `NTSTATUS` exit codes instead of signals.
+#[cfg(windows)]
+pub fn exit_reason(status: &ExitStatus) -> ExitReason {
+    let exit_code = status.code();
+    let description = match exit_code.map(|code| code as u32) {
+        Some(0xC0000005) => "access violation, out of bounds access".to_string(),
+        Some(0xC00000FD) => "stack overflow".to_string(),
+        Some(0xC0000094) => "integer division by zero".to_string(),
+        Some(0xC000008E) => "floating point division by zero".to_string(),
+        Some(0xC0000409) => "aborted, by a failed assertion or a buffer overrun".to_string(),
+        Some(0xC0000017) => "out of memory".to_string(),
+        _ => format!("exited with code {}", exit_code.unwrap_or(-1)),
+    };
+    ExitReason {
+        signal: None,
+        description,
+    }
+}
+
+/// What is started for a solution, the same way for compiled binaries and
+/// for interpreters running a source file.
+#[derive(Clone, Debug)]
+pub struct RunPlan {
+    pub program: String,
+    pub args: Vec<String>,
+    pub working_dir: PathBuf,
+    pub env: HashMap<String, String>,
+}
+
+/// Spawns a solution with its stdin read from `input_file`, its stdout written
+/// to `output_file` and its stderr to `error_file` or discarded, killing it
+/// once it goes over `limits`. The output limit applies to each file.
+pub trait Runner: Send + Sync {
+    fn run(
+        &self,
+        plan: &RunPlan,
+        input_file: &Path,
+        output_file: &Path,
+        error_file: Option<&Path>,
+        limits: &RunLimits,
+    ) -> Result<RunResult, String>;
+}
+
+/// Picks the runner for the current platform, sandboxing solutions inside
+/// `sandbox_workspace` when it is given. `parallel_runs` is how many solutions
+/// the runner may be running at the same time. Solutions are killed once
+/// `cancel` is set.
+pub fn native_runner(
+    sandbox_workspace: Option<&Path>,
+    parallel_runs: usize,
+    cancel: CancelToken,
+) -> Result<Box<dyn Runner>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let sandbox = sandbox_workspace
+            .map(|workspace| Sandbox::new(workspace, parallel_runs))
+            .transpose()?;
+        Ok(Box::new(PosixRunner { sandbox, cancel }))
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        let _ = parallel_runs;
+        match sandbox_workspace {
+            Some(_) => Err("the sandbox is only supported on linux".into()),
+            None => Ok(Box::new(PosixRunner { cancel })),
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = parallel_runs;
+        match sandbox_workspace {
+            Some(_) => Err("the sandbox is only supported on linux".into()),
+            None => Ok(Box::new(WindowsRunner { cancel })),
+        }
+    }
+}
+
+/// Runs `cmd` to the end like `Command::output`, but kills it and returns
+/// `None` once `cancel` is set, so that a long build can be cancelled.
+pub fn output_unless_cancelled(
+    cmd: &mut Command,
+    cancel: &CancelToken,
+) -> io::Result<Option<Output>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // compilers and build tools run their steps as child processes, which
+        // are killed along with them through the group
+        cmd.process_group(0);
+    }
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if cancel.is_cancelled() {
+            kill_all(&mut child);
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(BUILD_POLL_INTERVAL);
+    };
+    Ok(Some(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+/// Reads a pipe to the end on a thread of its own, so that a program printing
+/// a lot cannot block on a full pipe while it is waited for.
+pub fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+#[cfg(unix)]
+fn kill_all(child: &mut Child) {
+    unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+}
+
+#[cfg(not(unix))]
+fn kill_all(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Keeps a console window from popping up for every spawned process on windows.
+pub fn no_window(command: &mut Command) -> &mut Command {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000);
+    }
+    command
+}
+
+fn build_command(
+    plan: &RunPlan,
+    input_file: &Path,
+    output_file: &Path,
+    error_file: Option<&Path>,
+) -> Result<Command, String> {
+    let stdin =
+        File::open(input_file).map_err(|err| format!("error while opening input file: {}", err))?;
+    let stdout = File::create(output_file)
+        .map_err(|err| format!("error while creating output file: {}", err))?;
+    let stderr = match error_file {
+        Some(error_file) => Stdio::from(
+            File::create(error_file)
+                .map_err(|err| format!("error while creating error file: {}", err))?,
+        ),
+        None => Stdio::null(),
+    };
+
+    let mut cmd = Command::new(&plan.program);
+    cmd.args(&plan.args)
+        .envs(&plan.env)
+        .current_dir(&plan.working_dir)
+        .stdin(Stdio::from(stdin))
+        .stdout(Stdio::from(stdout))
+        .stderr(stderr);
+    Ok(cmd)
+}
+
+/// Checks the limits that can be observed the same way on every platform.
+/// `written` are the files the solution's output goes to.
+fn exceeded_limit(
+    start: Instant,
+    written: &[&Path],
+    limits: &RunLimits,
+    cancel: &CancelToken,
+) -> Option<Termination> {
+    if cancel.is_cancelled() {
+        return Some(Termination::Cancelled);
+    }
+    if start.elapsed() > limits.timeout {
+        return Some(Termination::TimedOut);
+    }
+    if let Some(limit) = limits.output_limit {
+        if written
+            .iter()
+            .any(|file| fs::metadata(file).is_ok_and(|m| m.len() > limit))
+        {
+            return Some(Termination::OutputLimitExceeded);
+        }
+    }
+    None
+}
+
+/// Cuts the output files back to the limit, as the solution may have written
+/// past it before it was killed.
+fn truncate_output(written: &[&Path], limits: &RunLimits) -> Result<(), String> {
+    let Some(limit) = limits.output_limit else {
+        return Ok(());
+    };
+    for file in written {
+        let file = File::options()
+            .write(true)
+            .open(file)
+            .map_err(|err| format!("error while truncating output file: {}", err))?;
+        if file.metadata().is_ok_and(|m| m.len() > limit) {
+            file.set_len(limit)
+                .map_err(|err| format!("error while truncating output file: {}", err))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub struct PosixRunner {
+    #[cfg(target_os = "linux")]
+    sandbox: Option<Sandbox>,
+    cancel: CancelToken,
+}
+
+#[cfg(unix)]
+impl PosixRunner {
+    /// Polls the child with `wait4` so that its resource usage can be read
+    /// when it is reaped, killing its whole process group early when it goes
+    /// over a limit.
+    fn wait(
+        &self,
+        mut cmd: Command,
+        written: &[&Path],
+        limits: &RunLimits,
+    ) -> Result<RunResult, String> {
+        use std::os::unix::process::{CommandExt, ExitStatusExt};
+
+        cmd.process_group(0);
+        let now = Instant::now();
+        let child = cmd
+            .spawn()
+            .map_err(|err| format!("error while running solution: {}", err))?;
+        let pid = child.id() as libc::pid_t;
+
+        let mut killed_for = None;
+        loop {
+            let mut status = 0;
+            let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+            let ret = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) };
+
+            if ret == pid {
+                // anything the solution forked is still around in its group
+                unsafe { libc::kill(-pid, libc::SIGKILL) };
+
+                if libc::WIFSIGNALED(status) {
+                    match libc::WTERMSIG(status) {
+                        libc::SIGXCPU => killed_for = killed_for.or(Some(Termination::TimedOut)),
+                        libc::SIGXFSZ => {
+                            killed_for = killed_for.or(Some(Termination::OutputLimitExceeded))
+                        }
+                        _ => {}
+                    }
+                }
+                if let Some(Termination::OutputLimitExceeded) = killed_for {
+                    truncate_output(written, limits)?;
+                }
+                return Ok(RunResult {
+                    termination: killed_for
+                        .unwrap_or(Termination::Exited(ExitStatus::from_raw(status))),
+                    time: now.elapsed(),
+                    cpu_time: Some(cpu_time(&usage)),
+                    memory: Some(max_rss_kilobytes(&usage)),
+                });
+            }
+            if ret < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(format!("error while waiting for solution: {}", err));
+            }
+
+            if killed_for.is_none() {
+                killed_for = exceeded_limit(now, written, limits, &self.cancel);
+                if killed_for.is_none() {
+                    if let (Some(limit), Some(peak)) = (limits.memory_limit, peak_memory(pid)) {
+                        if peak > limit {
+                            killed_for = Some(Termination::MemoryLimitExceeded);
+                        }
+                    }
+                }
+                if killed_for.is_some() {
+                    unsafe { libc::kill(-pid, libc::SIGKILL) };
+                }
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Runner for PosixRunner {
+    fn run(
+        &self,
+        plan: &RunPlan,
+        input_file: &Path,
+        output_file: &Path,
+        error_file: Option<&Path>,
+        limits: &RunLimits,
+    ) -> Result<RunResult, String> {
+        #[allow(unused_mut)]
+        let mut cmd = build_command(plan, input_file, output_file, error_file)?;
+        #[cfg(target_os = "linux")]
+        if let Some(sandbox) = &self.sandbox {
+            sandbox.apply(&mut cmd, &plan.working_dir, limits)?;
+        }
+        let written: Vec<&Path> = [Some(output_file), error_file]
+            .into_iter()
+            .flatten()
+            .collect();
+        self.wait(cmd, &written, limits)
+    }
+}
+
+#[cfg(unix)]
+fn cpu_time(usage: &libc::rusage) -> Duration {
+    let timeval = |t: libc::timeval| {
+        Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64)
+    };
+    timeval(usage.ru_utime) + timeval(usage.ru_stime)
+}
+
+#[cfg(target_os = "macos")]
+fn max_rss_kilobytes(usage: &libc::rusage) -> u64 {
+    usage.ru_maxrss as u64 / 1024
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn max_rss_kilobytes(usage: &libc::rusage) -> u64 {
+    usage.ru_maxrss as u64
+}
+
+/// Reads the high water mark of the resident set of a running process.
+#[cfg(target_os = "linux")]
+fn peak_memory(pid: libc::pid_t) -> Option<u64> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn peak_memory(_pid: libc::pid_t) -> Option<u64> {
+    None
+}
+
+/// Windows has no `wait4`, so neither cpu time nor memory are measured and
+/// only the time and output limits are enforced here.
+#[cfg(windows)]
+pub struct WindowsRunner {
+    cancel: CancelToken,
+}
+
+#[cfg(windows)]
+impl Runner for WindowsRunner {
+    fn run(
+        &self,
+        plan: &RunPlan,
+        input_file: &Path,
+        output_file: &Path,
+        error_file: Option<&Path>,
+        limits: &RunLimits,
+    ) -> Result<RunResult, String> {
+        let mut cmd = build_command(plan, input_file, output_file, error_file)?;
+        no_window(&mut cmd);
+        let written: Vec<&Path> = [Some(output_file), error_file]
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let now = Instant::now();
+        let mut child = cmd
+            .spawn()
+            .map_err(|err| format!("error while running solution: {}", err))?;
+
+        let termination = loop {
+            let status = child
+                .try_wait()
+                .map_err(|err| format!("error while waiting for solution: {}", err))?;
+            if let Some(status) = status {
+                break Termination::Exited(status);
+            }
+
+            if let Some(termination) = exceeded_limit(now, &written, limits, &self.cancel) {
+                child
+                    .kill()
+                    .map_err(|err| format!("error while killing solution: {}", err))?;
+                child
+                    .wait()
+                    .map_err(|err| format!("error while waiting for solution: {}", err))?;
+                if let Termination::OutputLimitExceeded = termination {
+                    truncate_output(&written, limits)?;
+                }
+                break termination;
+            }
+
+            std::thread::sleep(POLL_INTERVAL);
+        };
+
+        Ok(RunResult {
+            termination,
+            time: now.elapsed(),
+            cpu_time: None,
+            memory: None,
+        })
+    }
+}
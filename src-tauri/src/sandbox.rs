@@ -0,0 +1,198 @@
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use std::ptr;
+
+use crate::runner::RunLimits;
+
+/// How many processes and threads a sandboxed solution may start on top of
+/// the ones the user already has running, as `RLIMIT_NPROC` counts them all.
+/// Solutions run in parallel share the same count, so each of them gets this
+/// much headroom.
+const EXTRA_PROCESSES: u64 = 64;
+
+/// Runs solutions in their own user, network and mount namespaces with the
+/// workspace mounted read-only and rlimits on cpu time, processes and file size.
+pub struct Sandbox {
+    workspace: CString,
+    remount_flags: libc::c_ulong,
+    uid_map: Vec<u8>,
+    gid_map: Vec<u8>,
+    uid: libc::uid_t,
+    /// processes and threads allowed on top of the running ones
+    extra_processes: u64,
+}
+
+impl Sandbox {
+    /// `parallel_runs` is how many solutions may be running at the same time.
+    pub fn new(workspace: &Path, parallel_runs: usize) -> Result<Sandbox, String> {
+        let workspace = workspace
+            .canonicalize()
+            .map_err(|err| format!("error while resolving workspace for sandbox: {}", err))?;
+        let workspace = CString::new(workspace.as_os_str().as_bytes())
+            .map_err(|err| format!("error while resolving workspace for sandbox: {}", err))?;
+
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(workspace.as_ptr(), &mut stat) } != 0 {
+            return Err(format!(
+                "error while reading workspace mount flags: {}",
+                io::Error::last_os_error()
+            ));
+        }
+        // a remount inside a user namespace has to keep the flags that are
+        // locked on the original mount
+        let mut remount_flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+        for (st_flag, ms_flag) in [
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ] {
+            if stat.f_flag & st_flag != 0 {
+                remount_flags |= ms_flag;
+            }
+        }
+
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Ok(Sandbox {
+            workspace,
+            remount_flags,
+            uid_map: format!("{} {} 1", uid, uid).into_bytes(),
+            gid_map: format!("{} {} 1", gid, gid).into_bytes(),
+            uid,
+            extra_processes: EXTRA_PROCESSES * parallel_runs.max(1) as u64,
+        })
+    }
+
+    /// Sets `cmd` up to enter the sandbox right before it execs the solution.
+    pub fn apply(
+        &self,
+        cmd: &mut Command,
+        working_dir: &Path,
+        limits: &RunLimits,
+    ) -> Result<(), String> {
+        let working_dir = CString::new(working_dir.as_os_str().as_bytes())
+            .map_err(|err| format!("error while setting up sandbox: {}", err))?;
+        let workspace = self.workspace.clone();
+        let remount_flags = self.remount_flags;
+        let uid_map = self.uid_map.clone();
+        let gid_map = self.gid_map.clone();
+        // counted for every run, the tasks of the solutions still running
+        // on other workers are part of it
+        let max_processes = running_tasks(self.uid) + self.extra_processes;
+        let cpu_seconds = limits.time_limit.as_secs_f64().ceil() as u64 + 1;
+        let output_limit = limits.output_limit;
+
+        // everything the closure needs is allocated up front, since only
+        // async-signal-safe calls are allowed between fork and exec
+        unsafe {
+            cmd.pre_exec(move || {
+                set_limit(libc::RLIMIT_CPU, cpu_seconds)?;
+                set_limit(libc::RLIMIT_NPROC, max_processes)?;
+                if let Some(output_limit) = output_limit {
+                    set_limit(libc::RLIMIT_FSIZE, output_limit)?;
+                }
+
+                check(libc::unshare(
+                    libc::CLONE_NEWUSER | libc::CLONE_NEWNET | libc::CLONE_NEWNS,
+                ))?;
+                write_file(c"/proc/self/setgroups", b"deny")?;
+                write_file(c"/proc/self/uid_map", &uid_map)?;
+                write_file(c"/proc/self/gid_map", &gid_map)?;
+
+                check(libc::mount(
+                    ptr::null(),
+                    c"/".as_ptr(),
+                    ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    ptr::null(),
+                ))?;
+                check(libc::mount(
+                    workspace.as_ptr(),
+                    workspace.as_ptr(),
+                    ptr::null(),
+                    libc::MS_BIND | libc::MS_REC,
+                    ptr::null(),
+                ))?;
+                check(libc::mount(
+                    ptr::null(),
+                    workspace.as_ptr(),
+                    ptr::null(),
+                    remount_flags,
+                    ptr::null(),
+                ))?;
+
+                // the working directory was entered before the remount, so it
+                // still points into the writable mount
+                check(libc::chdir(working_dir.as_ptr()))?;
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+}
+
+fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(target_env = "gnu")]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(target_env = "gnu"))]
+type Resource = libc::c_int;
+
+fn set_limit(resource: Resource, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    };
+    check(unsafe { libc::setrlimit(resource, &limit) })
+}
+
+fn write_file(path: &CStr, data: &[u8]) -> io::Result<()> {
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY);
+        check(fd)?;
+        let written = libc::write(fd, data.as_ptr().cast(), data.len());
+        libc::close(fd);
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Counts the processes and threads owned by `uid`.
+fn running_tasks(uid: libc::uid_t) -> u64 {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join("status")).ok())
+        .filter(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Uid:"))
+                .and_then(|ids| ids.split_whitespace().next())
+                .and_then(|id| id.parse::<libc::uid_t>().ok())
+                == Some(uid)
+        })
+        .filter_map(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Threads:"))
+                .and_then(|threads| threads.trim().parse::<u64>().ok())
+        })
+        .sum()
+}
@@ -1,4 +1,4 @@
-use std::fmt::{Display, Formatter};
+use std::fmt::Formatter;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -7,13 +7,18 @@ use std::{fmt, fs};
 
 use serde::{Deserialize, Serialize};
 
+use crate::checker::Checker;
+use crate::custom_tests::CustomTests;
+use crate::judge::JudgeConfig;
+use crate::language::{LanguageRegistry, LanguageSpec};
 use crate::problem::*;
 
 #[derive(Serialize, Deserialize)]
 pub struct Store {
     pub contest_type: ContestType,
     pub problem_types: Vec<ProblemId>,
-    pub language: Language,
+    /// id of a language in the registry
+    pub language: String,
     pub directory: String,
     pub show_solved: bool,
     #[serde(skip)]
@@ -25,6 +30,13 @@ pub struct Store {
     #[serde(skip)]
     pub index: usize,
     pub editor: String,
+    #[serde(default)]
+    pub judge_config: JudgeConfig,
+    #[serde(skip)]
+    pub languages: LanguageRegistry,
+    /// why the user's languages could not be loaded, shown once the app is up
+    #[serde(skip)]
+    pub languages_error: Option<String>,
 }
 
 pub struct StoreState(pub Mutex<Store>);
@@ -49,7 +61,7 @@ impl Store {
                 ProblemId::G,
                 ProblemId::H,
             ],
-            language: Language::Cpp,
+            language: "cpp".into(),
             directory,
             show_solved: true,
             problems_list: None,
@@ -57,16 +69,30 @@ impl Store {
             solved_problems: None,
             index: 0,
             editor: String::new(),
+            judge_config: JudgeConfig::default(),
+            languages: LanguageRegistry::default(),
+            languages_error: None,
         }
     }
 
     pub fn read_or_create() -> Result<Store, String> {
         let data_dir = tauri::api::path::data_dir().unwrap();
-        if Path::new(&format!("{}/xcoder_store.json", data_dir.display())).exists() {
-            Store::read()
+        let mut store = if Path::new(&format!("{}/xcoder_store.json", data_dir.display())).exists()
+        {
+            Store::read()?
         } else {
-            Store::create("".into())
+            Store::create("".into())?
+        };
+        // the registry is edited by hand, a mistake in it should not keep the
+        // app from starting
+        match LanguageRegistry::load() {
+            Ok(languages) => store.languages = languages,
+            Err(err) => {
+                store.languages = LanguageRegistry::default();
+                store.languages_error = Some(format!("{}, using the built-in languages", err));
+            }
         }
+        Ok(store)
     }
 
     pub fn create(directory: String) -> Result<Store, String> {
@@ -136,40 +162,82 @@ impl Store {
         }
     }
 
-    pub fn create_file(&self) -> Result<(), String> {
+    /// Where the solution of the current problem is kept for the current language.
+    pub fn solution_path(&self) -> Result<PathBuf, String> {
         let problem = self.get_problem()?;
         if let Problem::Bare(problem) = problem {
+            let language = self.language()?;
             let mut file_path = PathBuf::from(format!(
-                "{}/{}/{}{}_{}",
+                "{}/{}/{}",
                 self.directory,
-                self.language.source_directory(),
-                problem.contest_type.to_string().to_lowercase(),
-                problem.contest_id,
-                problem.problem_id,
+                language.source_directory,
+                problem.file_name(),
             ));
-            file_path.set_extension(self.language.extension());
-            if file_path.exists() {
-                return Ok(());
-            }
-            fs::create_dir_all(file_path.parent().unwrap())
-                .map_err(|err| format!("error while creating directory: {}", err))?;
-            File::create(file_path).map_err(|err| format!("error while creating file: {}", err))?;
-            Ok(())
+            file_path.set_extension(&language.extension);
+            Ok(file_path)
         } else {
             Err("got invalid problem while creating file".into())
         }
     }
 
+    /// Creates the solution file with `contents`, leaving an existing one alone.
+    pub fn create_file(&self, contents: &str) -> Result<(), String> {
+        let file_path = self.solution_path()?;
+        if file_path.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(file_path.parent().unwrap())
+            .map_err(|err| format!("error while creating directory: {}", err))?;
+        fs::write(file_path, contents).map_err(|err| format!("error while creating file: {}", err))
+    }
+
+    /// Overrides the checker of the current problem, `None` going back to the
+    /// default checker.
+    pub fn set_checker(&mut self, checker: Option<Checker>) -> Result<(), String> {
+        match self.get_problem()? {
+            Problem::Bare(problem) => {
+                match checker {
+                    Some(checker) => self
+                        .judge_config
+                        .checkers
+                        .insert(problem.file_name(), checker),
+                    None => self.judge_config.checkers.remove(&problem.file_name()),
+                };
+                Ok(())
+            }
+            _ => Err("got invalid problem while setting checker".into()),
+        }
+    }
+
+    pub fn get_checker(&self) -> Result<Checker, String> {
+        match self.get_problem()? {
+            Problem::Bare(problem) => Ok(self.judge_config.checker(&problem.file_name())),
+            _ => Err("got invalid problem while getting checker".into()),
+        }
+    }
+
+    pub fn custom_tests(&self) -> Result<CustomTests, String> {
+        match self.get_problem()? {
+            Problem::Bare(problem) => Ok(CustomTests::new(
+                &self.directory,
+                &problem.test_cases_name(),
+            )),
+            _ => Err("got invalid problem while getting custom tests".into()),
+        }
+    }
+
+    pub fn language(&self) -> Result<LanguageSpec, String> {
+        self.languages.get(&self.language).cloned()
+    }
+
     pub fn open_file_in_editor(&self) -> Result<(), String> {
         let problem = self.get_problem()?;
         if let Problem::Bare(problem) = problem {
             let file_path = PathBuf::from(format!(
-                "{}/{}/{}{}_{}",
+                "{}/{}/{}",
                 self.directory,
-                self.language.source_directory(),
-                problem.contest_type.to_string().to_lowercase(),
-                problem.contest_id,
-                problem.problem_id,
+                self.language()?.source_directory,
+                problem.file_name(),
             ));
             Command::new("code").arg(file_path);
             Ok(())
@@ -204,135 +272,3 @@ impl ContestType {
         }
     }
 }
-
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
-pub enum Language {
-    C,
-    Cpp,
-    Go,
-    Rust,
-    Kotlin,
-    Zig,
-    Node,
-    Swift,
-    Dart,
-    Haskell,
-    Elixir,
-    Fortran,
-    Ocaml,
-    Python,
-    Julia,
-    Fsharp,
-    Csharp,
-}
-
-#[derive(PartialEq, Eq)]
-pub enum LangType {
-    Compiled,
-    Interpreted,
-}
-
-impl Display for Language {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-impl Language {
-    pub fn from_str(str: &str) -> Result<Language, String> {
-        use Language::*;
-        match str.to_lowercase().as_str() {
-            "c" => Ok(C),
-            "cpp" => Ok(Cpp),
-            "go" => Ok(Go),
-            "rust" => Ok(Rust),
-            "kotlin" => Ok(Kotlin),
-            "zig" => Ok(Zig),
-            "node" => Ok(Node),
-            "swift" => Ok(Swift),
-            "dart" => Ok(Dart),
-            "haskell" => Ok(Haskell),
-            "elixir" => Ok(Elixir),
-            "fortran" => Ok(Fortran),
-            "ocaml" => Ok(Ocaml),
-            "python" => Ok(Python),
-            "julia" => Ok(Julia),
-            "c#" => Ok(Csharp),
-            "f#" => Ok(Fsharp),
-            _ => Err("invalid language".into()),
-        }
-    }
-
-    pub fn extension(&self) -> String {
-        use Language::*;
-        match self {
-            C => "c".into(),
-            Cpp => "cpp".into(),
-            Go => "go".into(),
-            Rust => "rs".into(),
-            Kotlin => "kt".into(),
-            Zig => "zig".into(),
-            Node => "js".into(),
-            Swift => "swift".into(),
-            Dart => "dart".into(),
-            Haskell => "hs".into(),
-            Elixir => "exs".into(),
-            Fortran => "f90".into(),
-            Ocaml => "ml".into(),
-            Python => "py".into(),
-            Julia => "jl".into(),
-            Fsharp => "fsx".into(),
-            Csharp => "cs".into(),
-        }
-    }
-
-    pub fn source_directory(&self) -> String {
-        use Language::*;
-        match self {
-            Elixir => String::from("lib"),
-            Rust => String::from("src/bin"),
-            Kotlin => String::from("src"),
-            Haskell => String::from("src"),
-            _ => String::from("."),
-        }
-    }
-
-    pub fn compiler(&self) -> Command {
-        use Language::*;
-        match self {
-            C => Command::new("gcc"),
-            Cpp => Command::new("g++"),
-            Rust => Command::new("rustc"),
-            Kotlin => Command::new("kotlinc"),
-            Zig => Command::new("zig"),
-            Swift => Command::new("swiftc"),
-            Dart => Command::new("dart"),
-            Haskell => Command::new("ghc"),
-            Fortran => Command::new("gfortran"),
-            Ocaml => Command::new("ocamlc"),
-            _ => panic!("compiler not found"),
-        }
-    }
-
-    pub fn interpreter(&self) -> String {
-        use Language::*;
-        match self {
-            Elixir => String::from("elixir"),
-            Node => String::from("node"),
-            Go => String::from("go run"),
-            Python => String::from("python"),
-            Julia => String::from("julia"),
-            Fsharp => String::from("dotnet fsx"),
-            Csharp => String::from("dotnet script"),
-            _ => panic!("interpretor not found"),
-        }
-    }
-
-    pub fn lang_type(&self) -> LangType {
-        use Language::*;
-        match self {
-            Python | Elixir | Go | Node | Fsharp => LangType::Interpreted,
-            _ => LangType::Compiled,
-        }
-    }
-}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::language::LanguageSpec;
+use crate::problem::{FullProblem, Problem};
+
+/// Contents of a new solution, read from `templates/<language>.<extension>` in
+/// the workspace with these placeholders filled in:
+///
+/// - `{{contest}}`, like `ABC123`
+/// - `{{problem_id}}`, like `A`
+/// - `{{title}}`
+/// - `{{url}}`
+/// - `{{time_limit}}`, in seconds
+/// - `{{memory_limit}}`, in megabytes
+/// - `{{date}}`, like `2024-01-31`
+///
+/// Without a template the solution starts out empty, and the problem is not
+/// scraped.
+pub async fn render(
+    directory: &str,
+    language: &LanguageSpec,
+    mut problem: Problem,
+) -> Result<String, String> {
+    let path = PathBuf::from(format!(
+        "{}/templates/{}.{}",
+        directory, language.id, language.extension
+    ));
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    let template = fs::read_to_string(&path)
+        .map_err(|err| format!("error while reading template: {}", err))?;
+
+    problem.scrape().await?;
+    match problem {
+        Problem::Full(problem) => Ok(fill(&template, &problem)),
+        _ => Err("error while getting full problem".into()),
+    }
+}
+
+fn fill(template: &str, problem: &FullProblem) -> String {
+    [
+        (
+            "{{contest}}",
+            format!("{}{:03}", problem.contest_type, problem.contest_id),
+        ),
+        ("{{problem_id}}", problem.problem_id.to_string()),
+        ("{{title}}", problem.title.clone()),
+        ("{{url}}", problem.url()),
+        ("{{time_limit}}", problem.time_limit.to_string()),
+        ("{{memory_limit}}", problem.memory_limit.to_string()),
+        ("{{date}}", today()),
+    ]
+    .iter()
+    .fold(template.to_string(), |text, (placeholder, value)| {
+        text.replace(placeholder, value)
+    })
+}
+
+/// The current date in UTC as `YYYY-MM-DD`.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86400) as i64;
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
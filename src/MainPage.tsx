@@ -16,15 +16,23 @@ import {
 } from "@mantine/core";
 import {
   create_file,
+  debug_run,
   get_contest_type,
   get_language,
+  get_languages,
+  get_languages_error,
+  detect_toolchains,
+  ToolchainStatus,
   get_problem,
   get_problem_type,
   get_show_solved,
   next,
   open_file,
+  prepare_submission,
   previous,
   run,
+  listen_judge_progress,
+  cancel_judge,
   set_contest_type,
   set_directory,
   set_editor,
@@ -34,25 +42,8 @@ import {
   submit,
 } from "./commands.tsx";
 import parse from "html-react-parser";
-import { IconLoader } from "@tabler/icons-react";
-
-const Languages = [
-  { label: "C", value: "c" },
-  { label: "C++", value: "cpp" },
-  { label: "Dart", value: "dart" },
-  { label: "Elixir", value: "elixir" },
-  { label: "Fortran", value: "fortran" },
-  { label: "F#", value: "f#" },
-  { label: "Go", value: "go" },
-  { label: "Haskell", value: "haskell" },
-  { label: "Julia", value: "julia" },
-  { label: "Kotlin", value: "kotlin" },
-  { label: "OCaml", value: "ocaml" },
-  { label: "Python", value: "python" },
-  { label: "Rust", value: "rust" },
-  { label: "Swift", value: "swift" },
-  { label: "Zig", value: "zig" },
-];
+import { notifications } from "@mantine/notifications";
+import { IconCheck, IconLoader } from "@tabler/icons-react";
 
 const PROBLEM_IDS = ["A", "B", "C", "D", "E", "F", "G", "H", "Ex"];
 
@@ -76,9 +67,15 @@ const MainPage = ({
     title: string;
   } | null>(null);
   let [language, setLanguage] = useState("cpp" as string);
+  let [languages, setLanguages] = useState(
+    [] as { label: string; value: string }[],
+  );
+  let [toolchains, setToolchains] = useState([] as ToolchainStatus[]);
   let [contest, setContest] = useState("ABC" as string);
   let [problem_ids, setProblemIds] = useState(PROBLEM_IDS as string[]);
   let [testing, setTesting] = useState(false);
+  let [progress, setProgress] = useState("");
+  let [session, setSession] = useState<number | null>(null);
   let [showSolved, setShowSolved] = useState(false);
   let [showResult, setShowResult] = useState("description" as string);
   let [resultDisabled, setResultDisabled] = useState(false);
@@ -91,7 +88,12 @@ const MainPage = ({
       answer: string;
       status: string;
       time: number;
+      cpu_time: number;
       memory: number;
+      stderr: string | null;
+      exit_code: number | null;
+      signal: string | null;
+      re_reason: string | null;
     }[]
   >([]);
 
@@ -120,19 +122,43 @@ const MainPage = ({
     await main_get_problem();
   };
 
+  useEffect(() => {
+    let unlisten = listen_judge_progress((progress) => {
+      if (progress.kind === "started") setSession(progress.session);
+      else if (progress.kind === "compiling") setProgress("Compiling");
+      else setProgress(`${progress.done}/${progress.total}`);
+    });
+    return () => {
+      unlisten.then((unlisten) => unlisten());
+    };
+  }, []);
+
   const onSubmit = async () => {
-    if (testing) return;
+    if (testing) return onCancel();
+    setProgress("");
     setTesting(true);
     let verdicts = await submit();
     setTesting(false);
+    setSession(null);
     handleVerdicts(verdicts ?? []);
   };
 
-  const onRun = async () => {
-    if (testing) return;
+  const onCancel = async () => {
+    if (session !== null) await cancel_judge(session);
+  };
+
+  const onRun = () => runSamples(run);
+
+  // sanitizers and debug assertions, to find out why a run crashed
+  const onDebugRun = () => runSamples(debug_run);
+
+  const runSamples = async (judge: typeof run) => {
+    if (testing) return onCancel();
+    setProgress("");
     setTesting(true);
-    let verdicts = await run();
+    let verdicts = await judge();
     setTesting(false);
+    setSession(null);
     handleVerdicts(verdicts ?? []);
   };
 
@@ -143,14 +169,24 @@ const MainPage = ({
       answer: string;
       status: string;
       time: number;
+      cpu_time: number;
       memory: number;
+      stderr: string | null;
+      exit_code: number | null;
+      signal: string | null;
+      re_reason: string | null;
     }[],
   ) => {
     console.log(verdicts);
     if (verdicts.length === 0) return;
-    verdicts.every((v) => v.status === "AC")
-      ? setFinalVerdict("Accepted")
-      : setFinalVerdict("Wrong Answer");
+    // OK is a custom test without an expected output that ran fine
+    if (verdicts.every((v) => v.status === "AC" || v.status === "OK"))
+      setFinalVerdict("Accepted");
+    else if (verdicts.some((v) => v.status === "SKIP"))
+      setFinalVerdict("Cancelled");
+    else if (verdicts.some((v) => v.status === "CE"))
+      setFinalVerdict("Compilation Error");
+    else setFinalVerdict("Wrong Answer");
     setVerdicts(verdicts);
     setResultDisabled(false);
     setShowResult("result");
@@ -207,10 +243,29 @@ const MainPage = ({
     await open_file();
   };
 
+  const onCopySubmission = async () => {
+    let submission = await prepare_submission();
+    if (submission === null) return;
+    await navigator.clipboard.writeText(submission.source);
+    notifications.show({
+      id: "submission_copied",
+      message: "solution copied, opening the submit page",
+      icon: <IconCheck size="1.1rem" />,
+      color: "teal",
+    });
+    open(submission.submit_url);
+  };
+
   useEffect(() => {
     appWindow.setTitle("XCoder");
     get_problem().then((v) => setProblem(v));
     get_language().then((v) => setLanguage(v));
+    get_languages_error();
+    get_languages().then((languages) =>
+      setLanguages(languages.map((x) => ({ label: x.name, value: x.id }))),
+    );
+    // detection waits on every compiler, the picker is usable before it ends
+    detect_toolchains().then((toolchains) => setToolchains(toolchains));
     get_contest_type().then((v) => setContest(v));
     get_problem_type().then((v) =>
       setProblemIds(v.map((x) => x.toUpperCase())),
@@ -410,7 +465,16 @@ const MainPage = ({
                   c={"white"}
                   label={"Language"}
                   className={"tracking-widest font-mono"}
-                  data={Languages}
+                  data={languages.map((x) => ({
+                    ...x,
+                    disabled: toolchains.some(
+                      (t) => t.language === x.value && t.error !== null,
+                    ),
+                  }))}
+                  description={
+                    toolchains.find((t) => t.language === language)?.version ??
+                    toolchains.find((t) => t.language === language)?.error
+                  }
                   checkIconPosition={"right"}
                   mb={3}
                   allowDeselect={false}
@@ -450,8 +514,11 @@ const MainPage = ({
               <Text className="font-mono" fz={"xl"} c={"purple"} mx={20}>
                 Time Taken: {verdicts[caseIndex]?.time?.toFixed(2)} sec{" "}
               </Text>
+              <Text className="font-mono" fz={"xl"} c={"purple"} mx={20}>
+                CPU Time: {verdicts[caseIndex]?.cpu_time?.toFixed(2) ?? "-"} sec{" "}
+              </Text>
               <Text className="font-mono" fz={"xl"} c={"blue"} mx={20}>
-                Memory : {verdicts[caseIndex]?.memory ?? "0"} MB{" "}
+                Memory : {verdicts[caseIndex]?.memory ?? "0"} KB{" "}
               </Text>
             </Group>
             <Group className="justify-between w-[100%]">
@@ -491,7 +558,16 @@ const MainPage = ({
               >
                 Output
                 <Textarea
-                  value={verdicts[caseIndex]?.output ?? ""}
+                  value={
+                    verdicts[caseIndex]?.status === "RE"
+                      ? [
+                          verdicts[caseIndex]?.re_reason,
+                          verdicts[caseIndex]?.stderr,
+                        ]
+                          .filter((x) => x)
+                          .join("\n\n")
+                      : verdicts[caseIndex]?.output ?? ""
+                  }
                   className={`h-[93%] w-full px-2 top-11 bg-[#3e3e3e]/50 rounded-md absolute`}
                   variant="unstyled"
                   maxRows={15}
@@ -522,6 +598,14 @@ const MainPage = ({
                     Open File
                   </Box>
                 )}
+                <Box
+                  c={"#c6c8cb"}
+                  onClick={onCopySubmission}
+                  className="px-8 py-2 mt-auto bg-white/30 rounded-lg cursor-pointer select-none
+                      hover:shadow hover:bg-[rgb(255 255 255 / 0.35);] active:bg-white/40"
+                >
+                  Copy
+                </Box>
               </Group>
 
               <Group ml={"auto"} mr={"xl"}>
@@ -531,7 +615,22 @@ const MainPage = ({
                   className="px-10 py-2 mx-10 bg-white/30 rounded-lg cursor-pointer select-none
                           hover:shadow hover:bg-[rgb(255 255 255 / 0.35);] active:bg-white/40"
                 >
-                  {testing ? <IconLoader size={"1.7rem"} /> : "Run"}
+                  {testing ? (
+                    <Group gap={4} wrap="nowrap">
+                      <IconLoader size={"1.7rem"} />
+                      {progress}
+                    </Group>
+                  ) : (
+                    "Run"
+                  )}
+                </Box>
+                <Box
+                  c={"#c6c8cb"}
+                  onClick={onDebugRun}
+                  className="px-6 py-2 bg-white/30 rounded-lg cursor-pointer select-none
+                          hover:shadow hover:bg-[rgb(255 255 255 / 0.35);] active:bg-white/40"
+                >
+                  {testing ? <IconLoader size={"1.7rem"} /> : "Debug"}
                 </Box>
                 <Box
                   c={"white"}
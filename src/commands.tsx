@@ -1,4 +1,5 @@
 import { invoke } from "@tauri-apps/api/tauri";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import { notifications } from "@mantine/notifications";
 import { IconCheck, IconX } from "@tabler/icons-react";
 
@@ -193,6 +194,300 @@ export const set_show_solved = async (show_solved: boolean) => {
   }
 };
 
+export type Checker =
+  | { kind: "tokens" }
+  | { kind: "strict" }
+  | { kind: "float"; absolute: number; relative: number }
+  | { kind: "case_insensitive" };
+
+export type JudgeConfig = {
+  output_limit: number;
+  sandbox: boolean;
+  time_limit_multiplier: number;
+  default_checker: Checker;
+  checkers: Record<string, Checker>;
+  workers: number;
+};
+
+export const get_judge_config = async () => {
+  try {
+    return (await invoke("get_judge_config")) as JudgeConfig;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_get_judge_config",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
+export const set_judge_config = async (judge_config: JudgeConfig) => {
+  try {
+    await invoke("set_judge_config", { judgeConfig: judge_config });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_set_judge_config",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
+export type Toolchain = {
+  compile: string[];
+  debug_compile: string[];
+  run: string[];
+  env: Record<string, string>;
+  version: string[];
+};
+
+export type LanguageSpec = Toolchain & {
+  id: string;
+  name: string;
+  extension: string;
+  source_directory: string;
+  compiled: boolean;
+  binary_extension: string | null;
+  atcoder_id: number | null;
+};
+
+export const get_languages = async () => {
+  try {
+    return (await invoke("get_languages")) as LanguageSpec[];
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_get_languages",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return [];
+  }
+};
+
+export const get_languages_error = async () => {
+  try {
+    let error = (await invoke("get_languages_error")) as string | null;
+    if (error !== null)
+      notifications.show({
+        id: "invalid_languages",
+        message: error,
+        icon: <IconX size="1.1rem" />,
+        color: "red",
+        autoClose: false,
+      });
+    return error;
+  } catch (e) {
+    console.error(e);
+    return null;
+  }
+};
+
+export type ToolchainStatus = {
+  language: string;
+  program: string | null;
+  path: string | null;
+  version: string | null;
+  error: string | null;
+};
+
+export const detect_toolchains = async () => {
+  try {
+    return (await invoke("detect_toolchains")) as ToolchainStatus[];
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_detect_toolchains",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return [];
+  }
+};
+
+export const get_toolchain = async (language: string) => {
+  try {
+    return (await invoke("get_toolchain", { language: language })) as Toolchain;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_get_toolchain",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
+export const set_toolchain = async (language: string, toolchain: Toolchain) => {
+  try {
+    await invoke("set_toolchain", { language: language, toolchain: toolchain });
+    notifications.show({
+      id: "toolchain_set",
+      message: "toolchain for " + language + " saved",
+      icon: <IconCheck size="1.1rem" />,
+      color: "teal",
+    });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_set_toolchain",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
+export const reset_toolchain = async (language: string) => {
+  try {
+    await invoke("reset_toolchain", { language: language });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_reset_toolchain",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
+export const get_checker = async () => {
+  try {
+    return (await invoke("get_checker")) as Checker;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_get_checker",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
+export const set_checker = async (checker: Checker | null) => {
+  try {
+    await invoke("set_checker", { checker: checker });
+    notifications.show({
+      id: "checker_set",
+      message: "checker set to " + (checker?.kind ?? "default"),
+      icon: <IconCheck size="1.1rem" />,
+      color: "teal",
+    });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_set_checker",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
+export type CustomTest = {
+  name: string;
+  input: string;
+  output: string | null;
+};
+
+export const list_custom_tests = async () => {
+  try {
+    return (await invoke("list_custom_tests")) as CustomTest[];
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_list_custom_tests",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return [];
+  }
+};
+
+export const add_custom_test = async (input: string, output: string | null) => {
+  try {
+    let test = (await invoke("add_custom_test", {
+      input: input,
+      output: output,
+    })) as CustomTest;
+    notifications.show({
+      id: "custom_test_added",
+      message: "added " + test.name,
+      icon: <IconCheck size="1.1rem" />,
+      color: "teal",
+    });
+    return test;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_add_custom_test",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
+export const edit_custom_test = async (test: CustomTest) => {
+  try {
+    await invoke("edit_custom_test", { test: test });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_edit_custom_test",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
+export const delete_custom_test = async (name: string) => {
+  try {
+    await invoke("delete_custom_test", { name: name });
+    notifications.show({
+      id: "custom_test_deleted",
+      message: "deleted " + name,
+      icon: <IconCheck size="1.1rem" />,
+      color: "teal",
+    });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_delete_custom_test",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};
+
 export const next = async () => {
   try {
     await invoke("next");
@@ -273,7 +568,12 @@ export const run = async () => {
       answer: string;
       status: string;
       time: number;
+      cpu_time: number;
       memory: number;
+      stderr: string | null;
+      exit_code: number | null;
+      signal: string | null;
+      re_reason: string | null;
     }[];
   } catch (e) {
     console.error(e);
@@ -287,6 +587,33 @@ export const run = async () => {
   }
 };
 
+export const debug_run = async () => {
+  try {
+    return (await invoke("debug_run")) as {
+      input: string;
+      output: string;
+      answer: string;
+      status: string;
+      time: number;
+      cpu_time: number;
+      memory: number;
+      stderr: string | null;
+      exit_code: number | null;
+      signal: string | null;
+      re_reason: string | null;
+    }[];
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_debug_run",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
 export const submit = async () => {
   try {
     return (await invoke("submit")) as {
@@ -295,7 +622,12 @@ export const submit = async () => {
       answer: string;
       status: string;
       time: number;
+      cpu_time: number;
       memory: number;
+      stderr: string | null;
+      exit_code: number | null;
+      signal: string | null;
+      re_reason: string | null;
     }[];
   } catch (e) {
     console.error(e);
@@ -309,6 +641,84 @@ export const submit = async () => {
   }
 };
 
+export type CompileResult = {
+  success: boolean;
+  exit_code: number | null;
+  stderr: string;
+  diagnostics: {
+    file: string;
+    line: number;
+    column: number | null;
+    severity: string;
+    message: string;
+  }[];
+};
+
+export type RunOutput = {
+  status: string;
+  stdout: string;
+  stderr: string;
+  exit_code: number | null;
+  time: number | null;
+  cpu_time: number | null;
+  memory: number | null;
+  compile_result: CompileResult | null;
+};
+
+export const run_with_input = async (input: string) => {
+  try {
+    return (await invoke("run_with_input", { input: input })) as RunOutput;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_run_with_input",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
+export type StressOptions = {
+  iterations: number;
+  time_budget: number;
+};
+
+export type StressResult = {
+  iterations: number;
+  seed: number | null;
+  verdict: {
+    input: string;
+    output: string;
+    answer: string;
+    status: string;
+    time: number;
+    cpu_time: number;
+    memory: number;
+    stderr: string | null;
+    exit_code: number | null;
+    signal: string | null;
+    re_reason: string | null;
+  } | null;
+  test_name: string | null;
+};
+
+export const stress_test = async (options: StressOptions) => {
+  try {
+    return (await invoke("stress_test", { options: options })) as StressResult;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_stress_test",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
 export const create_file = async () => {
   try {
     await invoke("create_file");
@@ -353,6 +763,28 @@ export const open_file = async () => {
   }
 };
 
+export type Submission = {
+  source: string;
+  task_url: string;
+  submit_url: string;
+  language_id: number | null;
+};
+
+export const prepare_submission = async () => {
+  try {
+    return (await invoke("prepare_submission")) as Submission;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_prepare_submission",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return null;
+  }
+};
+
 export const save_state = async () => {
   try {
     await invoke("save_state");
@@ -368,3 +800,71 @@ export const save_state = async () => {
     return false;
   }
 };
+
+export type JudgeProgress =
+  | { kind: "started"; session: number }
+  | { kind: "compiling" }
+  | {
+      kind: "judging";
+      done: number;
+      total: number;
+      name: string;
+      status: string;
+    };
+
+export const listen_judge_progress = async (
+  onProgress: (progress: JudgeProgress) => void,
+) => {
+  let unlisteners: UnlistenFn[] = [];
+  let done = 0;
+  unlisteners.push(
+    await listen("judge-start", (event: { payload: { session: number } }) => {
+      onProgress({ kind: "started", session: event.payload.session });
+    }),
+  );
+  unlisteners.push(
+    await listen("judge-compile-start", () => {
+      done = 0;
+      onProgress({ kind: "compiling" });
+    }),
+  );
+  unlisteners.push(
+    await listen(
+      "judge-verdict",
+      (event: {
+        payload: {
+          index: number;
+          total: number;
+          name: string;
+          verdict: { status: string };
+        };
+      }) => {
+        done += 1;
+        onProgress({
+          kind: "judging",
+          done: done,
+          total: event.payload.total,
+          name: event.payload.name,
+          status: event.payload.verdict.status,
+        });
+      },
+    ),
+  );
+  return () => unlisteners.forEach((unlisten) => unlisten());
+};
+
+export const cancel_judge = async (session: number) => {
+  try {
+    await invoke("cancel_judge", { session: session });
+    return true;
+  } catch (e) {
+    console.error(e);
+    notifications.show({
+      id: "cannot_cancel",
+      message: e as string,
+      icon: <IconX size="1.1rem" />,
+      color: "red",
+    });
+    return false;
+  }
+};